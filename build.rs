@@ -5,10 +5,12 @@
 
   cc-rs bindgen
   and generate link option
+  (bundled ODE on windows, pkg-config or ODE_LIB_DIR on others)
 */
 
 extern crate cc;
 extern crate bindgen;
+extern crate pkg_config;
 
 use std::path::PathBuf;
 
@@ -33,7 +35,7 @@ fn main() {
 
   let _mk_cc = |dname: &str, sname: &str, iname: &str, oname: &str| {
     let sd = PathBuf::from(dname);
-    let fname = format!("{}", sd.join(sname).to_str().expect("invalid path"));
+    let fname = sd.join(sname).to_str().expect("invalid path").to_string();
     println!("cargo:rerun-if-changed={}", fname);
     cc::Build::new()
       .file(fname)
@@ -53,7 +55,7 @@ fn main() {
   let _mk_bindings = |hdd: &str, header: &str, rsd: &str, rsfile: &str,
     binl: bool, bcmt: bool| { // inline, comment
     let hd = PathBuf::from(hdd);
-    let hf = format!("{}", hd.join(header).to_str().expect("invalid path"));
+    let hf = hd.join(header).to_str().expect("invalid path").to_string();
     println!("cargo:rerun-if-changed={}", hf);
     let bindings = bindgen::Builder::default()
       .header(hf)
@@ -78,7 +80,50 @@ fn main() {
   }
 */
 
-  println!("cargo:rustc-link-search=./ode/lib");
-  println!("cargo:rustc-link-lib=drawstuff");
-  println!("cargo:rustc-link-lib=ode");
+  // ODE_LIB_DIR: override (drawstuff and ode in the directory)
+  // DRAWSTUFF_LIB_DIR: drawstuff when it is not beside ODE
+  // windows: bundled ./ode/lib (MinGW)
+  // others: system or vendored ODE found by pkg-config
+  // drawstuff is required by feature render (libode-dev does not have it,
+  // build it from the ODE source and set DRAWSTUFF_LIB_DIR)
+  println!("cargo:rerun-if-env-changed=ODE_LIB_DIR");
+  println!("cargo:rerun-if-env-changed=DRAWSTUFF_LIB_DIR");
+  let target_os = std::env::var("CARGO_CFG_TARGET_OS").unwrap_or_default();
+  if let Ok(d) = std::env::var("ODE_LIB_DIR") {
+    println!("cargo:rustc-link-search={}", d);
+    println!("cargo:rustc-link-lib=ode");
+  }else if target_os == "windows" {
+    println!("cargo:rustc-link-search=./ode/lib");
+    println!("cargo:rustc-link-lib=ode");
+  }else{
+    pkg_config::Config::new().probe("ode")
+      .expect("ODE not found (install libode-dev or set ODE_LIB_DIR)");
+  }
+  // feature render: drawstuff is checked and linked with GL GLU X11
+  // feature headless: nothing is required here, ode-rs (and the drawstuff
+  // crate) still ask for drawstuff, DRAWSTUFF_LIB_DIR only adds the search
+  let render = std::env::var("CARGO_FEATURE_RENDER").is_ok();
+  let has_ds = |d: &str| ["libdrawstuff.a", "libdrawstuff.so", "drawstuff.lib"]
+    .iter().any(|f| PathBuf::from(d).join(f).exists());
+  let ds_dir = std::env::var("DRAWSTUFF_LIB_DIR").ok()
+    .or(std::env::var("ODE_LIB_DIR").ok())
+    .or((target_os == "windows").then(|| "./ode/lib".to_string())); // MinGW
+  if render && !ds_dir.as_deref().is_some_and(has_ds) {
+    panic!("drawstuff not found in {} (libode-dev does not have drawstuff, \
+      build it from the ODE source and set DRAWSTUFF_LIB_DIR)",
+      ds_dir.as_deref().unwrap_or("DRAWSTUFF_LIB_DIR (not set)"));
+  }
+
+  let ds_env = std::env::var("DRAWSTUFF_LIB_DIR").ok();
+  if let Some(d) = &ds_env {
+    println!("cargo:rustc-link-search={}", d);
+  }
+  if render {
+    println!("cargo:rustc-link-lib=drawstuff");
+  }
+  if target_os != "windows" && (render || ds_env.is_some()) { // drawstuff on X11
+    for lib in ["GL", "GLU", "X11"] {
+      println!("cargo:rustc-link-lib={}", lib);
+    }
+  }
 }
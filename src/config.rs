@@ -0,0 +1,169 @@
+/*
+  config.rs for c60
+
+  evolution chain, drop pool and arena loaded from toml
+*/
+
+use serde::Deserialize;
+
+use ode_rs::ode::dReal;
+
+use std::collections::HashMap;
+use std::fs;
+
+use crate::polyhedron::*;
+
+/// evolution chain and drop pool
+#[derive(Debug, Clone)]
+pub struct Evolution {
+  /// pre evolution drop
+  pub ped: Vec<PE>,
+  /// evolution &lt;key, next&gt;
+  pub evo: HashMap<String, PE>
+}
+
+/// file format (PE as catalogue name, key as mesh key)
+#[derive(Debug, Deserialize)]
+struct EvolutionFile {
+  ped: Vec<String>,
+  evo: HashMap<String, String>
+}
+
+impl Default for Evolution {
+  /// built-in evolution chain
+  fn default() -> Self {
+    let pe = |n: &str| PE::from_name(n).expect(n);
+    Evolution{
+      ped: ["ERSphere", "ETetra", "ECubeCenter", "EOcta", "ECone"]
+        .into_iter().map(pe).collect(),
+      evo: vec![
+        ("r_sphere", "ETetra"),
+        ("tetra", "ECubeCenter"),
+        ("cube_center", "EOcta"),
+        ("octa", "ECone"),
+        ("cone", "EIcosahedronN0"),
+        ("icosahedron", "EPin"),
+        ("pin", "EDodecahedronCenterN0"),
+        ("dodecahedron_center", "ERing"),
+        ("ring", "EC60CenterN0"),
+        ("c60_center", "ERSphere")
+      ].into_iter().map(|(s, n)| (s.to_string(), pe(n))).collect()}
+  }
+}
+
+impl Evolution {
+  /// parse toml and check every key is a tms! mesh and every target a PE
+  pub fn from_toml(s: &str) -> Result<Self, String> {
+    let f: EvolutionFile = toml::from_str(s).map_err(|e| e.to_string())?;
+    let pe = |n: &String| PE::from_name(n).ok_or(format!("unknown PE: {}", n));
+    let ped = f.ped.iter().map(pe).collect::<Result<Vec<_>, _>>()?;
+    if ped.is_empty() { return Err("ped is empty".to_string()); }
+    let mut evo = HashMap::new();
+    for (k, n) in f.evo.iter() {
+      if !PE::is_key(k) { return Err(format!("unknown mesh key: {}", k)); }
+      evo.insert(k.to_string(), pe(n)?);
+    }
+    Ok(Evolution{ped, evo})
+  }
+
+  /// load from toml file
+  pub fn load(path: &str) -> Result<Self, String> {
+    let s = fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+    Evolution::from_toml(&s).map_err(|e| format!("{}: {}", path, e))
+  }
+}
+
+/// game arena (walled container built from MetaBox panels)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct Arena {
+  /// inner size x y and wall height z
+  pub size: [dReal; 3],
+  /// wall thickness
+  pub thick: dReal,
+  /// centre x y
+  pub center: [dReal; 2],
+  /// drop zone height above the walls
+  pub drop: dReal,
+  /// piece radius kept off the walls by the hold pos bounds
+  pub radius: dReal
+}
+
+impl Default for Arena {
+  fn default() -> Self {
+    Arena{size: [8.0, 8.0, 5.0], thick: 0.2, center: [0.0, 0.0], drop: 2.0,
+      radius: 1.0}
+  }
+}
+
+impl Arena {
+  /// parse toml (missing fields are default)
+  pub fn from_toml(s: &str) -> Result<Self, String> {
+    let a: Arena = toml::from_str(s).map_err(|e| e.to_string())?;
+    a.check()?;
+    Ok(a)
+  }
+
+  /// check size thick and radius
+  pub fn check(&self) -> Result<(), String> {
+    if self.size.iter().any(|&v| v <= 0.0) || self.thick <= 0.0 {
+      return Err("arena size and thick must be positive".to_string());
+    }
+    if self.radius < 0.0 {
+      return Err("arena radius must not be negative".to_string());
+    }
+    Ok(())
+  }
+
+  /// load from toml file
+  pub fn load(path: &str) -> Result<Self, String> {
+    let s = fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+    Arena::from_toml(&s).map_err(|e| format!("{}: {}", path, e))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn evolution_from_toml() {
+    let e = Evolution::from_toml(
+      "ped = [\"ETetra\", \"ECone\"]\n[evo]\ntetra = \"EC60CenterN0\"\n")
+      .expect("evolution");
+    assert_eq!(e.ped, vec![PE::from_name("ETetra").unwrap(),
+      PE::from_name("ECone").unwrap()]);
+    assert_eq!(e.evo["tetra"].key(), "c60_center");
+  }
+
+  #[test]
+  fn evolution_rejects_unknown_pe() {
+    let r = Evolution::from_toml("ped = [\"ETetra\", \"EBall\"]\n[evo]\n");
+    assert_eq!(r.unwrap_err(), "unknown PE: EBall");
+    let r = Evolution::from_toml("ped = [\"ETetra\"]\n[evo]\ntetra = \"ECube2\"\n");
+    assert_eq!(r.unwrap_err(), "unknown PE: ECube2");
+  }
+
+  #[test]
+  fn evolution_rejects_unknown_mesh_key() {
+    let r = Evolution::from_toml("ped = [\"ETetra\"]\n[evo]\nETetra = \"ECone\"\n");
+    assert_eq!(r.unwrap_err(), "unknown mesh key: ETetra");
+  }
+
+  #[test]
+  fn evolution_rejects_empty_ped() {
+    assert!(Evolution::from_toml("ped = []\n[evo]\n").is_err());
+  }
+
+  #[test]
+  fn resources_load() {
+    Evolution::load("./resources/evolution.toml").expect("evolution.toml");
+    Arena::load("./resources/arena.toml").expect("arena.toml");
+  }
+
+  #[test]
+  fn default_evolution_is_in_the_catalogue() {
+    let e = Evolution::default();
+    assert!(e.evo.keys().all(|k| PE::is_key(k)));
+  }
+}
@@ -0,0 +1,763 @@
+/*
+  game.rs for c60
+
+  polyhedron merge game state machine on SimApp
+*/
+
+use ode_rs::ode::*;
+
+use std::ffi::{c_void}; // used by impl_sim_fn
+use impl_sim::{impl_sim_fn, impl_sim_derive};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use std::collections::{HashMap, VecDeque};
+use std::time;
+
+use crate::polyhedron::*;
+use crate::score::{Score, MergeEvent};
+use crate::config::{Evolution, Arena};
+use crate::scenes::*;
+use crate::scenefile::SceneFile;
+use crate::snapshot::Snapshot;
+use crate::replay::{Replay, Session};
+use crate::registry::*;
+use crate::merge::{EBP, Spawn, SCarry};
+
+/// help for application defined keys
+pub const APP_HELP: &str = "
+  application defined key set (this app, not passed to the default key set)
+  '0': drop trimesh bunny
+  '1': drop trimesh tetra
+  '2': drop trimesh cube
+  '3': drop trimesh icosahedron
+  '4': drop tmball (tmbunny) (over calc when 3 tmbunnys at the same position)
+  '5': drop test composite
+  '6': drop test box small
+  '7': drop c60 icosahedron
+  '8': drop c60 dodecahedron
+  '9': drop c60 fullerene
+  '@': drop polyhedron (sequence)
+  'h': left
+  'j': front
+  'k': back
+  'l': right
+  'X': rotate about X
+  'Y': rotate about Y
+  'Z': rotate about Z
+  'R': reset rotation
+  'H': hold or swap current (once per drop)
+  'c': collision info
+  'x': collision info sub
+  ' ': drop apple ball
+  't': torque
+  'o': big ball info
+  'b': test mut (big ball)
+  'a': test cmd (all info)
+  'S': score
+  'r': restart game
+  'e': write snapshot
+  'E': read snapshot
+  'u': undo last drop";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[repr(usize)]
+pub enum Phase {
+  PEmpty, PHold, PRelease, PDown, PGameOver, PEnd
+}
+pub use Phase::*;
+
+impl Phase {
+  pub fn from_usize(u: usize) -> Option<Self> {
+    const PS: [Phase; Phase::PEnd as usize] = [
+      PEmpty, PHold, PRelease, PDown, PGameOver];
+    if u >= Phase::PEnd as usize { return None; }
+    Some(PS[u])
+  }
+}
+
+/// SimApp
+pub struct SimApp {
+  /// phase
+  pub(crate) phase: Phase,
+  /// current hold key
+  pub(crate) current: String,
+  /// current hold pos
+  pub(crate) pos: dVector3,
+  /// current hold orientation (kept when released)
+  pub(crate) rot: dQuaternion,
+  /// hold pos bounds xmin xmax ymin ymax (None: not limited)
+  pub(crate) bounds: Option<[dReal; 4]>,
+  /// scene built by start_callback
+  pub(crate) scene: Scene,
+  /// game arena
+  pub(crate) arena: Arena,
+  /// scene file built after the scene
+  pub(crate) scene_file: Option<SceneFile>,
+  /// next drop pe choose from the seeded PRNG
+  pub(crate) rng: ChaCha8Rng,
+  /// seed of rng (reported to reproduce the drop order)
+  pub(crate) seed: u64,
+  /// next drop keys (preview queue, front is the next)
+  pub(crate) nexkeys: VecDeque<String>,
+  /// number of previews
+  pub(crate) nexn: usize,
+  /// next drop disp pos (front of the queue)
+  pub(crate) nexpos: dVector3,
+  /// disp pos offset of each following preview
+  pub(crate) nexoff: dVector3,
+  /// hold slot key
+  pub(crate) holdkey: Option<String>,
+  /// hold slot disp pos
+  pub(crate) holdpos: dVector3,
+  /// true after hold or swap until the next drop
+  pub(crate) swapped: bool,
+  /// pre evolution drop
+  pub(crate) ped: Vec<PE>,
+  /// evolution &lt;key, next&gt;
+  pub(crate) evo: HashMap<String, PE>,
+  /// erase body pairs
+  pub(crate) ebps: Vec<EBP>,
+  /// typed object registry
+  pub(crate) reg: Registry,
+  /// how the merged polyhedron starts
+  pub(crate) spawn: Spawn,
+  /// merge connected groups of three or more at once
+  pub(crate) cluster: bool,
+  /// merge events of the last step
+  pub(crate) events: Vec<MergeEvent>,
+  /// score
+  pub(crate) score: Score,
+  /// released pieces (keys) checked by the height limit line
+  pub(crate) pieces: Vec<String>,
+  /// height limit line (z, seconds a settled piece may stay above)
+  pub(crate) limit: (dReal, dReal),
+  /// true after set_limit (the arena keeps the limit line)
+  pub(crate) limit_set: bool,
+  /// seconds a settled piece stays above the limit line
+  pub(crate) over: dReal,
+  /// simulation steps (not paused)
+  pub(crate) steps: usize,
+  /// command recording and playback
+  pub(crate) replay: Replay,
+  /// snapshot path written by 'e' and read by 'E'
+  pub(crate) snap_path: String,
+  /// snapshot restored after the scene is built
+  pub(crate) snap_start: Option<Snapshot>,
+  /// snapshots taken just before each release (last is the newest)
+  pub(crate) undo: VecDeque<Snapshot>,
+  /// number of drops that can be undone
+  pub(crate) undo_depth: usize,
+  /// collision info
+  pub(crate) i: bool,
+  /// collision info sub
+  pub(crate) j: bool,
+  pub(crate) t: time::Instant,
+  pub(crate) n: usize,
+  pub(crate) u: usize,
+  pub(crate) cnt: usize
+}
+
+impl Default for SimApp {
+  fn default() -> Self {
+    SimApp::new()
+  }
+}
+
+impl SimApp {
+
+/// construct with the default drop pool and evolution chain
+pub fn new() -> Self {
+  let Evolution{ped, evo} = Evolution::default();
+  let score = Score::new(&evo, &ped);
+  let seed: u64 = rand::thread_rng().gen();
+  SimApp{
+    phase: PEmpty, current: "".to_string(), pos: [-2.0, 0.0, 6.0, 1.0],
+    rot: QI,
+    bounds: None, scene: SGame, arena: Arena::default(), scene_file: None,
+    rng: ChaCha8Rng::seed_from_u64(seed), seed,
+    nexkeys: VecDeque::new(), nexn: 3,
+    nexpos: [-2.0, 0.0, 10.0, 1.0], nexoff: [0.0, 3.0, 0.0, 0.0],
+    holdkey: None, holdpos: [-2.0, -4.0, 10.0, 1.0], swapped: false,
+    ped, evo,
+    ebps: vec![], reg: Registry::default(), spawn: SCarry, cluster: false, events: vec![],
+    score,
+    pieces: vec![], limit: (4.0, 3.0), limit_set: false, over: 0.0,
+    steps: 0, replay: Replay::new(seed),
+    snap_path: "./snapshot.toml".to_string(), snap_start: None,
+    undo: VecDeque::new(), undo_depth: 8,
+    i: false, j: false,
+    t: time::Instant::now(), n: PE::COUNT, u: 0, cnt: 0}
+}
+
+/// score (running total and merge counts per tier)
+pub fn score(&self) -> &Score {
+  &self.score
+}
+
+/// replace the evolution chain and drop pool (score tiers follow)
+pub fn set_evolution(&mut self, e: Evolution) {
+  let w = self.score.window;
+  self.score = Score::new(&e.evo, &e.ped);
+  self.score.window = w; // keep --combo given before --evo
+  self.ped = e.ped;
+  self.evo = e.evo;
+}
+
+/// reseed rng (same seed gives the same drop order)
+pub fn set_seed(&mut self, seed: u64) {
+  self.rng = ChaCha8Rng::seed_from_u64(seed);
+  self.seed = seed;
+  self.replay.seed = seed;
+}
+
+/// seed used by rng
+pub fn seed(&self) -> u64 {
+  self.seed
+}
+
+/// record commands to path (header written at start, commands as they come)
+pub fn record_replay(&mut self, path: &str) {
+  self.replay.record_to(path);
+}
+
+/// session options of this run (written in the replay header)
+pub fn session(&self) -> Session {
+  Session{scene: Some(self.scene), spawn: Some(self.spawn),
+    cluster: Some(self.cluster), arena: Some(self.arena.clone()),
+    evo: Some(Evolution{ped: self.ped.clone(), evo: self.evo.clone()}),
+    undo: Some(self.undo_depth), combo: Some(self.score.window),
+    limit: Some((self.limit_set.then_some(self.limit.0), self.limit.1)),
+    scene_file: Some(self.scene_file.as_ref().map(|f| f.hash()))}
+}
+
+/// play back commands of the replay at the same steps
+/// (seed scene spawn cluster arena and evolution in the header are applied,
+/// error when undo combo limit or scene file differ from this run)
+pub fn play_replay(&mut self, r: Replay) -> Result<(), String> {
+  let (rs, ss) = (&r.session, self.session());
+  let differ = |o: &str, h: String, n: String|
+    format!("replay recorded with {} {} (this run {})", o, h, n);
+  if let Some(u) = rs.undo.filter(|u| Some(*u) != ss.undo) {
+    return Err(differ("--undo", u.to_string(), self.undo_depth.to_string()));
+  }
+  if let Some(c) = rs.combo.filter(|c| Some(*c) != ss.combo) {
+    return Err(differ("--combo", c.to_string(), self.score.window.to_string()));
+  }
+  if let Some(l) = rs.limit.filter(|l| Some(*l) != ss.limit) {
+    return Err(differ("--limit", format!("{:?}", l), format!("{:?}", ss.limit)));
+  }
+  if let Some(h) = rs.scene_file.filter(|h| Some(*h) != ss.scene_file) {
+    return Err(differ("--scene-file hash", format!("{:?}", h),
+      format!("{:?}", ss.scene_file)));
+  }
+  self.set_seed(r.seed);
+  let rs = r.session;
+  if let Some(sc) = rs.scene { self.set_scene(sc); }
+  if let Some(sp) = rs.spawn { self.set_spawn(sp); }
+  if let Some(c) = rs.cluster { self.set_cluster(c); }
+  if let Some(a) = rs.arena { self.set_arena(a); }
+  if let Some(e) = rs.evo { self.set_evolution(e); }
+  self.replay.play = r.play;
+  Ok(())
+}
+
+/// set game arena (built by scene game)
+pub fn set_arena(&mut self, a: Arena) {
+  self.arena = a;
+}
+
+/// set height limit line (z, seconds a settled piece may stay above)
+pub fn set_limit(&mut self, z: dReal, sec: dReal) {
+  self.limit = (z, sec);
+  self.limit_set = true;
+}
+
+/// merge events of the last step
+pub fn events(&self) -> &[MergeEvent] {
+  &self.events
+}
+
+/// typed object registry
+pub fn registry(&self) -> &Registry {
+  &self.reg
+}
+
+/// register body with kind and role (tier from the evolution chain)
+pub fn register(&mut self, id: dBodyID, pe: Option<PE>, role: Role) {
+  let tier = pe.and_then(|pe| self.score.tier(pe.key()));
+  self.reg.insert(id, Entry::new(pe, tier, self.steps, role));
+}
+
+/// register body found by key
+pub fn register_key(&mut self, k: &str, pe: Option<PE>, role: Role) {
+  let Ok(o) = self.super_get().find(k.to_string()) else { return; };
+  let id = o.body();
+  self.register(id, pe, role);
+}
+
+/// unregister from the registry and ODE (with destroy)
+pub fn destroy(&mut self, id: dBodyID) {
+  self.reg.remove(id);
+  self.super_mut().unregister_obg_by_id(id, true);
+}
+
+pub fn ts(&mut self, s: &str) -> String {
+  format!("{}_{:016x}", s, self.t.elapsed().as_nanos())
+}
+
+pub fn kgc(&mut self, s: &str) {
+  let rode = self.super_mut();
+  let Ok(o) = rode.find(s.to_string()) else { return; }; // get not mut
+  let krp = rode.get_krp_mut(o.geom());
+  krp.k = true;
+  krp.g = true;
+  krp.c = true;
+  let Ok(o) = rode.find_mut(s.to_string()) else { return; }; // re get mut
+  o.enable();
+}
+
+pub fn proc_key(&mut self, cmd: i32) {
+  if self.phase == PHold {
+    match cmd as u8 as char {
+    'h' => { self.pos[1] -= 0.1; self.trans(); }, // -Y left
+    'j' => { self.pos[0] += 0.1; self.trans(); }, // +X front
+    'k' => { self.pos[0] -= 0.1; self.trans(); }, // -X back
+    'l' => { self.pos[1] += 0.1; self.trans(); }, // +Y right
+    'X' => { self.rotate([1.0, 0.0, 0.0]); }, // about world X
+    'Y' => { self.rotate([0.0, 1.0, 0.0]); }, // about world Y
+    'Z' => { self.rotate([0.0, 0.0, 1.0]); }, // about world Z
+    'R' => { self.rot = self.default_rot(); self.trans(); },
+    _ => {}
+    }
+  }
+}
+
+/// rotate hold orientation about the world axis by PI / 12
+pub fn rotate(&mut self, axis: [dReal; 3]) {
+  let p = dQuaternion::from_axis_and_angle(axis, PI / 12.0);
+  self.rot = dQuaternion::multiply0(p, self.rot);
+  self.trans();
+}
+
+/// catalogue default orientation of the current piece
+pub fn default_rot(&self) -> dQuaternion {
+  let Ok(o) = self.super_get().find(self.current.clone()) else { return QI; };
+  self.reg.get(o.body()).and_then(|e| e.pe).map_or(QI, |pe| pe.def().o.q())
+}
+
+/// keep hold pos inside bounds (the drop zone above the arena)
+pub fn clamp_pos(&mut self) {
+  let Some([x0, x1, y0, y1]) = self.bounds else { return; };
+  self.pos[0] = self.pos[0].max(x0).min(x1);
+  self.pos[1] = self.pos[1].max(y0).min(y1);
+}
+
+pub fn trans(&mut self) {
+  self.clamp_pos();
+  let ck = self.current.clone(); // clone to skip borrow
+  let q = self.pos; // copy to skip borrow
+  let r = self.rot;
+  let rode = self.super_mut();
+  let Ok(o) = rode.find_mut(ck) else { return; };
+  o.pos_()[..4].copy_from_slice(&q);
+  o.set_quaternion(r);
+  self.update_guide();
+}
+
+/// set number of previews (at least 1)
+pub fn set_preview(&mut self, n: usize) {
+  self.nexn = n.max(1);
+}
+
+/// disp pos of the preview i
+pub fn preview_pos(&self, i: usize) -> dVector3 {
+  let mut p = self.nexpos;
+  for (v, o) in p.iter_mut().zip(self.nexoff.iter()).take(3) {
+    *v += o * i as dReal;
+  }
+  p
+}
+
+/// fill the preview queue (rng draws into the tail)
+pub fn set_next(&mut self) {
+  while self.nexkeys.len() < self.nexn {
+    let nexpe = self.ped[self.rng.gen_range(0..self.ped.len())];
+    let p = self.preview_pos(self.nexkeys.len());
+    let k = self.create_polyhedron(nexpe.0, p);
+    self.nexkeys.push_back(k);
+  }
+}
+
+/// move each preview to its disp pos (after the queue shifted)
+pub fn shift_next(&mut self) {
+  let ps: Vec<(String, dVector3)> = self.nexkeys.iter().enumerate()
+    .map(|(i, k)| (k.clone(), self.preview_pos(i))).collect();
+  for (k, q) in ps.into_iter() {
+    let Ok(o) = self.super_mut().find_mut(k) else { continue; };
+    o.pos_()[..4].copy_from_slice(&q);
+  }
+}
+
+/// current from the front of the preview queue
+/// pop the next piece as current (false when the queue is empty)
+pub fn take_next(&mut self) -> bool {
+  let Some(k) = self.nexkeys.pop_front() else { return false; };
+  self.current = k;
+  self.rot = self.default_rot();
+  self.trans();
+  self.shift_next();
+  self.set_next();
+  true
+}
+
+pub fn set_current(&mut self) {
+  if self.phase != PEmpty && self.phase != PDown { return; }
+  if !self.take_next() { return; }
+  self.phase = PHold;
+  self.update_guide(); // after PHold (hidden while taking next)
+  self.swapped = false;
+}
+
+/// park the piece at p without collision (Krp as the preview)
+pub fn park(&mut self, k: &str, p: dVector3) {
+  let rode = self.super_mut();
+  let Ok(o) = rode.find(k.to_string()) else { return; };
+  let (id, g) = (o.body(), o.geom());
+  let krp = rode.get_krp_mut(g);
+  krp.k = false;
+  krp.g = false;
+  krp.c = false;
+  let q = self.reg.get(id).and_then(|e| e.pe).map_or(QI, |pe| pe.def().o.q());
+  self.set_pos_Q(id, p, q);
+  Obg::set_linear_vel_by_id(id, &[0.0; 4]);
+  Obg::set_angular_vel_by_id(id, &[0.0; 4]);
+}
+
+/// store current in the hold slot, or swap with the held one
+/// (once per drop)
+pub fn hold_current(&mut self) {
+  if self.phase != PHold || self.swapped { return; }
+  let ck = self.current.clone(); // clone to skip borrow
+  self.park(&ck, self.holdpos);
+  match self.holdkey.replace(ck) {
+  None => { self.take_next(); },
+  Some(h) => {
+    self.current = h;
+    self.rot = self.default_rot();
+    self.trans();
+  }
+  }
+  self.swapped = true;
+}
+
+pub fn release_current(&mut self) {
+  if self.phase != PHold { return; }
+  self.push_undo(); // PHold -> PRelease
+  let ck = self.current.clone(); // clone to skip borrow
+  self.kgc(&ck);
+  self.pieces.push(ck);
+  self.phase = PRelease;
+  self.update_guide();
+}
+
+/// check the height limit line (game over when a settled piece stays above)
+pub fn check_limit(&mut self) {
+  if self.phase == PGameOver { return; }
+  let (z, sec) = self.limit;
+  let rode = self.super_get();
+  let t_delta = rode.t_delta;
+  let above = self.pieces.iter().any(|k| {
+    let Ok(o) = rode.find(k.to_string()) else { return false; };
+    let v = Obg::get_linear_vel_mut_by_id(o.body());
+    let settled = v[..3].iter().all(|a| a.abs() < 0.1);
+    settled && Obg::get_pos_mut_by_id(o.body())[2] > z
+  });
+  if !above { self.over = 0.0; return; }
+  self.over += t_delta;
+  if self.over >= sec {
+    println!("game over: a piece stays above z={} for {} sec", z, sec);
+    print!("{}", self.score);
+    self.phase = PGameOver;
+  }
+}
+
+/// forget the game state (pieces, queue, hold, score and undo)
+/// the bodies must be destroyed before
+pub fn clear_game(&mut self) {
+  self.current.clear();
+  self.nexkeys.clear();
+  self.holdkey = None;
+  self.swapped = false;
+  self.pieces.clear();
+  self.ebps.clear();
+  self.events.clear();
+  self.undo.clear();
+  self.score.reset();
+  self.over = 0.0;
+  self.phase = PEmpty;
+}
+
+/// clear the game pieces (released, current and next) and start a new game
+pub fn restart(&mut self) {
+  let mut ks = self.pieces.clone();
+  ks.push(self.current.clone());
+  ks.extend(self.nexkeys.drain(..));
+  ks.extend(self.holdkey.take());
+  for k in ks {
+    let Ok(o) = self.super_get().find(k) else { continue; };
+    let id = o.body();
+    self.destroy(id);
+  }
+  self.clear_game();
+  println!("restart");
+  self.set_next();
+  self.set_current();
+}
+
+pub fn objs_mut(&mut self, f: bool, s: &str) {
+  let rode = self.super_mut();
+  if f || rode.is_modified(false) {
+    self.cnt = rode.num();
+    println!("obgs: {} in {}", self.cnt, s);
+    let rode = self.super_get(); // must re get
+    let ids = rode.each_id(|_key, _id| { true }); // lambda may return false
+    for id in ids {
+      if id == 0 as dBodyID { continue; } // skipped by result of each_id
+      let rode = self.super_mut(); // must re get
+      match rode.get_mut(id) {
+        Err(e) => { println!("{}", e); },
+        Ok(obg) => {
+          // This is test code using each_id with get_mut, but high cost.
+          // Better to use self.super_mut().find_mut("ball_big".to_string())
+          if obg.key == "ball_big" { obg.col = [1.0, 0.0, 0.0, 0.8]; }
+          println!("{}: {:018p} {:?}", obg.key, id, obg.col);
+          // get_tcm_mut must be after accessing to obg members
+          if obg.key == "ball_big" {
+            let geom = obg.geom(); // must assign before get_tcm_mut
+            let mgm = rode.get_mgm_mut(geom).unwrap(); // must care ok_or
+            mgm.get_tcm_mut().col = [1.0, 0.0, 0.0, 0.8];
+          }
+        }
+      }
+    }
+  }
+}
+
+pub fn objs_info(&mut self, f: bool, s: &str) {
+  let rode = self.super_mut();
+  if f || rode.is_modified(false) {
+    self.cnt = rode.num();
+    println!("obgs: {} in {}", self.cnt, s);
+    if !f { return; }
+    let rode = self.super_get(); // must re get because borrow later self.cnt
+    rode.each(|key, id, obg| {
+      println!("{}: {:018p} {:?}", key, id, obg.col);
+      true
+    });
+  }
+}
+
+}
+
+#[impl_sim_derive(draw_geom, stop_callback)] // near_callback
+impl Sim for SimApp {
+
+fn draw_objects(&mut self) {
+  self.objs_info(false, "draw"); // twice (after step)
+/*
+  let ds = ODE::ds_as_ref();
+  ds.SetDrawMode(1); // test always wireframe
+*/
+  self.super_mut().draw_objects();
+}
+
+fn start_callback(&mut self) {
+  let t_delta = &mut self.super_mut().t_delta;
+  *t_delta = 0.002;
+  self.reg.clear(); // world is empty (at start and after the default reset)
+  self.clear_game();
+  self.create_scene();
+
+  self.phase = PEmpty;
+  if self.is_game() {
+    println!("seed: {}", self.seed);
+    self.create_guide();
+    self.set_next();
+    self.set_current();
+  }
+  if let Some(s) = self.snap_start.take() {
+    if let Err(e) = self.restore(&s) { println!("{}", e); }
+  }
+  let ss = self.session();
+  self.replay.set_session(ss);
+  if let Err(e) = self.replay.start() { println!("{}", e); }
+
+  self.super_mut().start_callback();
+}
+
+fn near_callback(&mut self, dat: *mut c_void, o1: dGeomID, o2: dGeomID) {
+  self.super_mut().near_callback(dat, o1, o2);
+
+  let (ck, phase) = (self.current.clone(), self.phase); // clone to skip borrow
+  let rode = self.super_mut(); // must re get mut (for get_contacts)
+  if rode.is_space(o1) || rode.is_space(o2) { return; } // skip when space
+  // check 'phase PDown' contains vs ground
+  let _contactgroup = rode.get_contactgroup(); // now do nothing
+  let n = rode.get_contacts(o1, o2);
+  if n == 0 { return; } // skip no collision
+  let (b1p, b1gp) = rode.get_ancestor(o1);
+  let (b2p, b2gp) = rode.get_ancestor(o2);
+  if b1p == b2p { return; } // may not arrive here
+  if phase == PRelease {
+    let b1 = if b1p != 0 as dBodyID { b1p } else { b1gp };
+    let b2 = if b2p != 0 as dBodyID { b2p } else { b2gp };
+    for b in [b1, b2].into_iter() {
+      if let Ok(o) = rode.get(b) {
+        if o.key == ck { println!("Down: {}", ck); self.phase = PDown; break; }
+      }
+    }
+  }
+
+  let (info, info_sub) = (self.i, self.j); // clone to skip borrow
+  let kind = self.reg.mergeable(b1p, b2p); // before re get mut
+  let rode = self.super_mut(); // must re get mut (for get_contacts)
+  let ground = rode.get_ground();
+  if ground == o1 || ground == o2 { return; } // skip vs ground
+  let Some(k) = kind else { return; }; // not mergeable or not the same kind
+  let contacts = rode.ref_contacts(); // or rode.ref_contacts_mut()
+  if info {
+    println!(" {:?} {:04} {:?} {}", b1p, n, b2p, k);
+    if info_sub {
+      for (i, c) in contacts.iter().enumerate() {
+        if i >= n as usize { break; }
+        // &Vec<dContact> dContactGeom dGeomID dReal
+        println!("  {:04} {:?}({:?}) {:?}({:?}) {:8.3e}",
+          i,
+          c.geom.g1, rode.get_grand_parent(c.geom.g1),
+          c.geom.g2, rode.get_grand_parent(c.geom.g2),
+          c.geom.depth);
+      }
+    }
+  }
+  // this code must be after contacts.iter() because of borrow mut self
+  self.ebps.push((b1p, b2p, k.to_string()));
+}
+
+fn step_callback(&mut self, pause: i32) {
+  if pause == 0 {
+    for cmd in self.replay.take(self.steps) { self.command_callback(cmd); }
+  }
+  self.objs_info(false, "step"); // twice (before draw)
+  self.super_mut().step_callback(pause);
+  if pause == 0 { self.hold_walls(); self.steps += 1; }
+  self.events.clear();
+  if !self.is_game() { self.ebps.clear(); return; } // no merges
+  for (bs, k) in self.resolve_merges().into_iter() { // each body at most once
+    let ev = self.merge_group(&bs, &k);
+    self.events.push(ev);
+  }
+  self.ebps.clear();
+  self.check_limit();
+  self.set_current();
+}
+
+fn command_callback(&mut self, cmd: i32) {
+  self.replay.record(self.steps, cmd);
+  match cmd as u8 as char {
+    '0' => { self.create_tmbunny(); },
+    '1' => { self.create_tmtetra(); },
+    '2' => { self.create_tmcube(); },
+    '3' => { self.create_tmicosahedron(); },
+    '4' => { self.create_tmball(); },
+    '5' => { self.create_test_composite(); },
+    '6' => { self.create_test_box_small(); },
+    '7' => { self.create_c60_icosahedron(); },
+    '8' => {
+      self.create_c60_dodecahedron();
+      self.create_c60_dodecahedron_center();
+    },
+    '9' => {
+      self.create_c60_fullerene();
+      self.create_c60_fullerene_center();
+    },
+    '@' if self.is_game() => {
+      self.release_current();
+    },
+    'h' if self.is_game() => { self.proc_key(cmd); }, // -Y left
+    'j' if self.is_game() => { self.proc_key(cmd); }, // +X front
+    'k' if self.is_game() => { self.proc_key(cmd); }, // -X back
+    'l' if self.is_game() => { self.proc_key(cmd); }, // +Y right
+    'X' | 'Y' | 'Z' | 'R' if self.is_game() => { self.proc_key(cmd); },
+    'H' if self.is_game() => { self.hold_current(); },
+    'c' => { self.i = !self.i; }, // collision info
+    'x' => { self.j = !self.j; }, // collision info sub
+    ' ' => {
+      let k = "apple";
+      match self.super_mut().find_mut(k.to_string()) {
+        Err(e) => { println!("{}", e); },
+        Ok(obg) => {
+          if obg.is_enabled() { obg.disable(); } else { obg.enable(); }
+        }
+      }
+    },
+    't' => {
+      for k in ["ball_big", "box_small",
+        "apple", "roll", "tmball"] { // TODO: tmXX_timestamp will not be found
+        match self.super_mut().find_mut(k.to_string()) {
+          Err(e) => { println!("{}", e); },
+          Ok(obg) => {
+            // obg.add_rel_torque([-0.5, 0.0, 0.0]);
+            obg.add_rel_force_rel([-0.5, 0.0, 0.0], [0.0, 0.0, 0.5]);
+          }
+        }
+      }
+      for k in ["ball", "tmbunny", "tmtetra",
+        "tmcube", "tmicosahedron"] { // TODO: tmXX_timestamp will not be found
+        match self.super_mut().find_mut(k.to_string()) {
+          Err(e) => { println!("{}", e); },
+          Ok(obg) => {
+            // obg.add_rel_torque([0.0, 0.0, 0.5]);
+            obg.add_rel_force_rel([0.0, 0.0, 0.5], [0.5, 0.0, 0.0]);
+          }
+        }
+      }
+    },
+    'o' => {
+      let k = "ball_big";
+      match self.super_mut().find_mut(k.to_string()) {
+        Err(e) => { println!("{}", e); },
+        Ok(obg) => {
+          println!("{}: {:018p} {:?}", k, obg.body(), obg.col);
+          println!(" pos: {}", obg.pos_vec());
+          println!(" rot: {}", obg.rot_mat3());
+          let pos: &mut [dReal] = obg.pos_(); // re get mut
+          pos[0] += 0.2;
+          pos[1] += 0.2;
+          pos[2] = 5.0;
+        }
+      }
+    },
+    'b' => {
+      self.objs_mut(true, "mut");
+    },
+    'a' => {
+      self.objs_info(true, "cmd");
+    },
+    'S' if self.is_game() => {
+      print!("{}", self.score);
+    },
+    'r' if self.is_game() => {
+      self.restart();
+    },
+    'e' => { self.save_snapshot(); },
+    'E' => { self.load_snapshot(); },
+    'u' if self.is_game() => { self.undo(); },
+    '?' => {
+      println!("{}", APP_HELP);
+      self.super_mut().command_callback(cmd); // default key set too
+    },
+    _ => { self.super_mut().command_callback(cmd); } // not handled by app
+  }
+}
+
+} // impl Sim for SimApp
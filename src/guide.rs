@@ -0,0 +1,106 @@
+/*
+  guide.rs for c60
+
+  drop guide (thin cylinder from the held piece down to the first surface
+  found by an ODE ray query)
+*/
+
+use ode_rs::ode::*;
+use ode_base::ode::*; // raw FFI (ray query) not exported by ode_rs
+
+use std::ffi::c_void;
+
+use crate::game::{SimApp, PHold};
+
+/// key of the drop guide
+pub const GUIDE_KEY: &str = "drop_guide";
+
+/// radius of the drop guide
+pub const GUIDE_R: dReal = 0.02;
+
+/// ray query state passed through dSpaceCollide2
+struct RayHit {
+  /// the ray
+  ray: dGeomID,
+  /// bodies not hit (held piece, previews, hold slot and the guide)
+  skip: Vec<dBodyID>,
+  /// nearest distance from the ray origin
+  depth: Option<dReal>
+}
+
+/// near callback of the ray vs a space member
+/// (dSpaceCollide2(ray, space) calls back as (member, ray), either order
+/// is accepted and sub spaces are searched again)
+unsafe extern "C" fn ray_callback(dat: *mut c_void, o1: dGeomID, o2: dGeomID) {
+  let ray = (*(dat as *const RayHit)).ray;
+  let g = if o1 == ray { o2 } else if o2 == ray { o1 } else { return; };
+  if dGeomIsSpace(g) != 0 {
+    dSpaceCollide2(ray, g, dat, Some(ray_callback));
+    return;
+  }
+  let hit = &mut *(dat as *mut RayHit);
+  let b = dGeomGetBody(g); // the member (GeomTransform of a composite too)
+  if b != 0 as dBodyID && hit.skip.contains(&b) { return; }
+  let mut cg: dContactGeom = std::mem::zeroed();
+  let sz = std::mem::size_of::<dContactGeom>() as i32;
+  if dCollide(ray, g, 1, &mut cg, sz) == 0 { return; }
+  if hit.depth.is_none_or(|d| cg.depth < d) { hit.depth = Some(cg.depth); }
+}
+
+impl SimApp {
+
+/// create drop guide (hidden below the ground)
+/// category and collide bits are 0 so the space never pairs it with a geom
+/// (the default near_callback ignores Krp.c)
+pub fn create_guide(&mut self) {
+  let krp = Krp::new(false, false, false, 0.2, 0.3);
+  let mi_guide = MetaCylinder::new(0.001, GUIDE_R, 1.0, krp, 0,
+    [1.0, 1.0, 1.0, 0.4]);
+  let (body, geom, _) = self.super_mut().creator(GUIDE_KEY, mi_guide);
+  unsafe {
+    dGeomSetCategoryBits(geom, 0);
+    dGeomSetCollideBits(geom, 0);
+  }
+  self.set_pos_Q(body, [0.0, 0.0, -1.0, 1.0], QI);
+}
+
+/// distance from the hold pos straight down to the first surface
+/// (the hold pos z when nothing is found)
+pub fn drop_distance(&mut self) -> dReal {
+  let mut ks: Vec<String> = self.nexkeys.iter().cloned().collect();
+  ks.extend(self.holdkey.clone());
+  ks.push(self.current.clone());
+  ks.push(GUIDE_KEY.to_string());
+  let rode = self.super_get();
+  let skip = ks.into_iter()
+    .filter_map(|k| rode.find(k).ok().map(|o| o.body())).collect();
+  let p = self.pos;
+  let len = p[2] + 1.0; // through the ground
+  unsafe {
+    let space = dGeomGetSpace(rode.get_ground());
+    let ray = dCreateRay(0 as dSpaceID, len);
+    dGeomRaySet(ray, p[0], p[1], p[2], 0.0, 0.0, -1.0);
+    let mut hit = RayHit{ray, skip, depth: None};
+    dSpaceCollide2(ray, space as dGeomID,
+      &mut hit as *mut RayHit as *mut c_void, Some(ray_callback));
+    dGeomDestroy(ray);
+    hit.depth.unwrap_or(p[2])
+  }
+}
+
+/// update drop guide (hidden below the ground unless PHold)
+pub fn update_guide(&mut self) {
+  let Ok(o) = self.super_get().find(GUIDE_KEY.to_string()) else { return; };
+  let (id, g) = (o.body(), o.geom());
+  if self.phase != PHold {
+    unsafe { dGeomCylinderSetParams(g, GUIDE_R, 1e-3); }
+    self.set_pos_Q(id, [0.0, 0.0, -1.0, 1.0], QI);
+    return;
+  }
+  let d = self.drop_distance().max(1e-3);
+  let p = self.pos;
+  unsafe { dGeomCylinderSetParams(g, GUIDE_R, d); }
+  self.set_pos_Q(id, [p[0], p[1], p[2] - d / 2.0, 1.0], QI);
+}
+
+}
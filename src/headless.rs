@@ -0,0 +1,81 @@
+/*
+  headless.rs for c60
+
+  drawstuff replacement without window (no OpenGL)
+  drives start step(s) stop callbacks for batch and CI
+*/
+
+use ode_rs::ode::Tdrawstuff;
+use ode_rs::ode::ds::dsFunctions_C;
+
+use std::ffi::{c_uint, c_int, c_char};
+
+/// drawstuff replacement that runs the simulation without any window
+pub struct Headless {
+  /// number of steps to run (step_callback is called with pause 0)
+  pub steps: usize
+}
+
+impl Headless {
+  /// construct
+  pub fn new(steps: usize) -> Self {
+    Headless{steps}
+  }
+}
+
+impl Tdrawstuff for Headless {
+  /// start_callback once, step_callback steps times, then stop_callback
+  /// (near_callback is dispatched by the world step as usual)
+  #[allow(clippy::not_unsafe_ptr_arg_deref)] // signature fixed by Tdrawstuff
+  fn SimulationLoop(&self, _argc: c_int, _argv: *mut *mut c_char,
+    _window_width: c_int, _window_height: c_int,
+    functions: *mut dsFunctions_C) {
+    let Some(f) = (unsafe { functions.as_ref() }) else { return; };
+    if let Some(start) = f.start { unsafe { start(); } }
+    if let Some(step) = f.step {
+      for _ in 0..self.steps { unsafe { step(0); } }
+    }
+    if let Some(stop) = f.stop { unsafe { stop(); } }
+  }
+  fn Debug(&self, _msg: *const c_char) {}
+  fn Error(&self, _msg: *const c_char) {}
+  fn Print(&self, _msg: *const c_char) {}
+  fn SetViewpoint(&self, _xyz: *mut f32, _hpr: *mut f32) {}
+  fn GetViewpoint(&self, _xyz: *mut f32, _hpr: *mut f32) {}
+  fn SetTexture(&self, _texture_number: c_int) {}
+  fn SetColor(&self, _red: f32, _green: f32, _blue: f32) {}
+  fn SetColorAlpha(&self, _red: f32, _green: f32, _blue: f32, _alpha: f32) {}
+  fn SetDrawMode(&self, _mode: c_int) {}
+  fn SetSphereQuality(&self, _n: c_int) {}
+  fn SetCapsuleQuality(&self, _n: c_int) {}
+  fn DrawBox(&self, _pos: *const f32, _rot: *const f32, _lxyz: *const f32) {}
+  fn DrawBoxD(&self, _pos: *const f64, _rot: *const f64, _lxyz: *const f64) {}
+  fn DrawSphere(&self, _pos: *const f32, _rot: *const f32, _radius: f32) {}
+  fn DrawSphereD(&self, _pos: *const f64, _rot: *const f64, _radius: f32) {}
+  fn DrawCylinder(&self, _pos: *const f32, _rot: *const f32,
+    _l: f32, _r: f32) {}
+  fn DrawCylinderD(&self, _pos: *const f64, _rot: *const f64,
+    _l: f32, _r: f32) {}
+  fn DrawCapsule(&self, _pos: *const f32, _rot: *const f32,
+    _l: f32, _r: f32) {}
+  fn DrawCapsuleD(&self, _pos: *const f64, _rot: *const f64,
+    _l: f32, _r: f32) {}
+  fn DrawLine(&self, _pos1: *const f32, _pos2: *const f32) {}
+  fn DrawLineD(&self, _pos1: *const f64, _pos2: *const f64) {}
+  fn DrawTriangle(&self, _pos: *const f32, _rot: *const f32,
+    _v0: *const f32, _v1: *const f32, _v2: *const f32, _solid: c_int) {}
+  fn DrawTriangleD(&self, _pos: *const f64, _rot: *const f64,
+    _v0: *const f64, _v1: *const f64, _v2: *const f64, _solid: c_int) {}
+  fn DrawTriangles(&self, _pos: *const f32, _rot: *const f32,
+    _v: *const f32, _n: c_int, _solid: c_int) {}
+  fn DrawTrianglesD(&self, _pos: *const f64, _rot: *const f64,
+    _v: *const f64, _n: c_int, _solid: c_int) {}
+  fn DrawConvex(&self, _pos: *const f32, _rot: *const f32,
+    _planes: *const f32, _planecount: c_uint,
+    _points: *const f32, _pointcount: c_uint, _polygons: *const c_uint) {}
+  fn DrawConvexD(&self, _pos: *const f64, _rot: *const f64,
+    _planes: *const f64, _planecount: c_uint,
+    _points: *const f64, _pointcount: c_uint, _polygons: *const c_uint) {}
+  fn ElapsedTime(&self) -> f64 { 0.0 }
+  fn Stop(&self) {}
+}
@@ -0,0 +1,48 @@
+#![doc(html_root_url = "https://docs.rs/c60/0.5.4")]
+//! c60 (Fullerene) on the ODE (Open Dynamics Engine) for Rust
+//!
+//! - game: polyhedron merge game state machine (SimApp, Phase)
+//! - polyhedron: polyhedron catalogue (PE) and spawning
+//! - scenes: named scenes and test scene builders
+//! - score: score model for polyhedron merges
+//! - config: evolution chain and drop pool loaded from toml
+//! - replay: command recording and playback
+//! - registry: typed object registry (dBodyID to kind tier step role)
+//! - merge: merge resolver for erase body pairs
+//! - headless: drawstuff replacement without window
+//! - scenefile: declarative scene file (toml)
+//! - snapshot: world snapshot save and load
+//! - guide: drop guide by an ODE ray query
+/*
+  cc-rs https://crates.io/crates/cc
+  bindgen https://crates.io/crates/bindgen
+
+  dependencies asciiz ode-rs
+
+  in the current directory
+    drawstuff.dll
+    ode.dll
+    libstdc++-6.dll
+    libgcc_s_seh-1.dll
+    libwinpthread-1.dll
+*/
+
+#[cfg(not(any(feature = "render", feature = "headless")))]
+compile_error!("enable feature render (default) or headless");
+
+#[cfg(all(feature = "render", feature = "headless"))]
+compile_error!("features render and headless are exclusive \
+  (use --no-default-features --features headless)");
+
+pub mod game;
+pub mod polyhedron;
+pub mod scenes;
+pub mod score;
+pub mod config;
+pub mod replay;
+pub mod registry;
+pub mod merge;
+pub mod headless;
+pub mod scenefile;
+pub mod snapshot;
+pub mod guide;
@@ -0,0 +1,276 @@
+/*
+  merge.rs for c60
+
+  merge resolver for erase body pairs and spawn of merged polyhedra
+*/
+
+use ph_faces::avg_f4;
+
+use ode_rs::ode::*;
+use ode_base::ode::dMass; // dMass::new (dMassSetZero)
+
+use std::cmp::Ordering;
+
+use crate::game::SimApp;
+use crate::polyhedron::PE;
+use crate::score::MergeEvent;
+
+/// erase body pair (body, body, mesh key)
+pub type EBP = (dBodyID, dBodyID, String);
+
+/// merge group (bodies of the same kind, mesh key)
+pub type MG = (Vec<dBodyID>, String);
+
+/// how the merged polyhedron starts
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Spawn {
+  /// zero velocity and catalogue default orientation
+  SCalm,
+  /// mass-weighted linear velocity, averaged angular velocity
+  /// and orientation blended from the pair
+  SCarry
+}
+pub use Spawn::*;
+
+impl Spawn {
+  /// all spawn modes
+  pub const ALL: [Spawn; 2] = [SCalm, SCarry];
+
+  /// name given on the command line
+  pub fn name(&self) -> &'static str {
+    match self {
+    SCalm => "calm",
+    SCarry => "carry"
+    }
+  }
+
+  /// spawn mode from name (None when not found)
+  pub fn from_name(s: &str) -> Option<Spawn> {
+    Spawn::ALL.into_iter().find(|sp| sp.name() == s)
+  }
+}
+
+/// motion carried into the merged polyhedron
+#[derive(Debug, Clone, Copy)]
+pub struct Carry {
+  /// linear velocity
+  pub lvel: [dReal; 3],
+  /// angular velocity
+  pub avel: [dReal; 3],
+  /// orientation
+  pub q: dQuaternion
+}
+
+impl Carry {
+  /// from the group (must be called before they are destroyed)
+  pub fn from_bodies(bs: &[dBodyID]) -> Self {
+    let (mut lvel, mut avel) = ([0.0; 3], [0.0; 3]);
+    let mut qs: Vec<[dReal; 4]> = vec![];
+    let mut mt: dReal = 0.0;
+    let n = bs.len() as dReal;
+    for &o in bs {
+      let mut mass = dMass::new();
+      Obg::get_mass_by_id(o, &mut mass);
+      let m = mass.mass;
+      let lv = Obg::get_linear_vel_mut_by_id(o);
+      let av = Obg::get_angular_vel_mut_by_id(o);
+      let q = Obg::get_quaternion_mut_by_id(o);
+      for i in 0..3 {
+        lvel[i] += m * lv[i];
+        avel[i] += av[i] / n;
+      }
+      mt += m;
+      qs.push([q[0], q[1], q[2], q[3]]);
+    }
+    if mt > 0.0 { for v in lvel.iter_mut() { *v /= mt; } }
+    // nlerp average (flip each onto the same hemisphere as the first)
+    let mut q: dQuaternion = [0.0; 4];
+    for p in qs.iter() {
+      let d: dReal = (0..4).map(|i| qs[0][i] * p[i]).sum();
+      let s = if d < 0.0 { -1.0 } else { 1.0 };
+      for i in 0..4 { q[i] += s * p[i]; }
+    }
+    let l = q.iter().map(|v| v * v).sum::<dReal>().sqrt();
+    if l > 0.0 { for v in q.iter_mut() { *v /= l; } } else { q = qs[0]; }
+    Carry{lvel, avel, q}
+  }
+
+  /// apply to the merged body
+  pub fn apply(&self, o: dBodyID) {
+    Obg::set_quaternion_by_id(o, &self.q);
+    Obg::set_linear_vel_by_id(o,
+      &[self.lvel[0], self.lvel[1], self.lvel[2], 0.0]);
+    Obg::set_angular_vel_by_id(o,
+      &[self.avel[0], self.avel[1], self.avel[2], 0.0]);
+  }
+}
+
+/// remove duplicate pairs and merge each body at most once
+/// (pairs ordered by order(a, b) = (distance, creation step) nearest first)
+pub fn resolve<F>(ebps: &[EBP], order: F) -> Vec<EBP>
+  where F: Fn(dBodyID, dBodyID) -> (dReal, usize) {
+  let mut ps: Vec<((dReal, usize), EBP)> = vec![];
+  for (a, b, k) in ebps.iter() {
+    let (a, b) = if (*a as usize) < (*b as usize) { (*a, *b) } else { (*b, *a) };
+    if ps.iter().any(|(_, (p, q, _))| *p == a && *q == b) { continue; }
+    ps.push((order(a, b), (a, b, k.clone())));
+  }
+  ps.sort_by(|(o, _), (p, _)| o.partial_cmp(p).unwrap_or(Ordering::Equal));
+  let mut used: Vec<dBodyID> = vec![];
+  let mut r = vec![];
+  for (_, (a, b, k)) in ps.into_iter() {
+    if used.contains(&a) || used.contains(&b) { continue; }
+    used.push(a);
+    used.push(b);
+    r.push((a, b, k));
+  }
+  r
+}
+
+/// connected groups of the same kind in contact (ebps as edges)
+pub fn clusters(ebps: &[EBP]) -> Vec<MG> {
+  let mut gs: Vec<MG> = vec![];
+  for (a, b, k) in ebps.iter() {
+    let ia = gs.iter().position(|(g, _)| g.contains(a));
+    let ib = gs.iter().position(|(g, _)| g.contains(b));
+    match (ia, ib) {
+    (None, None) => { gs.push((vec![*a, *b], k.clone())); },
+    (Some(i), None) => { gs[i].0.push(*b); },
+    (None, Some(j)) => { gs[j].0.push(*a); },
+    (Some(i), Some(j)) => {
+      if i == j { continue; }
+      let (g, _) = gs.remove(i.max(j));
+      gs[i.min(j)].0.extend(g);
+    }
+    }
+  }
+  gs
+}
+
+impl SimApp {
+
+/// merge connected groups of three or more at once (skip tiers by size)
+pub fn set_cluster(&mut self, f: bool) {
+  self.cluster = f;
+}
+
+/// set how the merged polyhedron starts
+pub fn set_spawn(&mut self, s: Spawn) {
+  self.spawn = s;
+}
+
+/// resolve ebps to merge groups (skip bodies already gone)
+/// pairs nearest centre first, or connected groups when cluster is set
+pub fn resolve_merges(&mut self) -> Vec<MG> {
+  let reg = &self.reg;
+  let ebps: Vec<EBP> = self.ebps.iter()
+    .filter(|(a, b, _)| reg.get(*a).is_some() && reg.get(*b).is_some())
+    .cloned().collect();
+  if self.cluster { return clusters(&ebps); }
+  resolve(&ebps, |a, b| {
+    let pa = Obg::get_pos_mut_by_id(a).to_vec();
+    let pb = Obg::get_pos_mut_by_id(b).to_vec();
+    let d = (0..3).map(|i| (pa[i] - pb[i]) * (pa[i] - pb[i])).sum::<dReal>();
+    let s = [a, b].iter().map(|&o| reg.get(o).map_or(0, |e| e.step)).max();
+    (d, s.unwrap_or(0))
+  }).into_iter().map(|(a, b, k)| (vec![a, b], k)).collect()
+}
+
+/// steps a merged body keeps the chain alive (combo window)
+pub fn set_combo_window(&mut self, steps: usize) {
+  self.score.window = steps;
+}
+
+/// merge group of the same kind k into the successor
+/// (advances bs.len() / 2 tiers, chains when a body spawned by a merge
+/// within the combo window is involved)
+pub fn merge_group(&mut self, bs: &[dBodyID], k: &str) -> MergeEvent {
+  println!("disappear {:?} {}", bs, k);
+  let carry = Carry::from_bodies(bs);
+  let (now, w) = (self.steps, self.score.window);
+  let chain = bs.iter().filter_map(|&o| self.reg.get(o))
+    .filter(|e| now <= e.step + w)
+    .filter_map(|e| e.chain).max().map_or(0, |c| c + 1);
+  let mut pos = vec![];
+  let mut keys = vec![];
+  for &o in bs {
+    if let Ok(obg) = self.super_get().get(o) { keys.push(obg.key.clone()); }
+    pos.push(Obg::get_pos_mut_by_id(o));
+    self.destroy(o);
+  }
+  self.pieces.retain(|pk| !keys.contains(pk));
+  let c = avg_f4(&pos);
+  // println!("{:?}", c);
+  let mut u = self.u;
+  let mut nek = k.to_string();
+  for _ in 0..bs.len() / 2 {
+    u = match self.evo.get(&nek) {
+    None => self.u,
+    Some(&u) => u.0
+    };
+    nek = PE(u).key().to_string();
+  }
+  let nk = self.create_polyhedron(u, c.try_into().unwrap()); // borrow temp
+  self.kgc(&nk);
+  if let Ok(o) = self.super_get().find(nk.clone()) {
+    let id = o.body();
+    if self.spawn == SCarry { carry.apply(id); }
+    self.reg.set_chain(id, chain);
+  }
+  self.pieces.push(nk.clone());
+  let pts = self.score.merge_n(k, bs.len(), chain);
+  let ev = MergeEvent{step: now, key: k.to_string(), n: bs.len(),
+    chain, mult: chain + 1, pts, spawned: nk};
+  println!("{} (score {})", ev, self.score.total);
+  ev
+}
+
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// fake body id (never dereferenced)
+  fn b(n: usize) -> dBodyID {
+    n as dBodyID
+  }
+
+  fn ebp(p: usize, q: usize, k: &str) -> EBP {
+    (b(p), b(q), k.to_string())
+  }
+
+  #[test]
+  fn resolve_dedup_and_order() {
+    // (2, 1) is (1, 2) reversed, 3 is nearest to 2, 1 and 4 are left
+    let ebps = vec![ebp(1, 2, "tetra"), ebp(2, 1, "tetra"),
+      ebp(2, 3, "tetra"), ebp(1, 4, "tetra")];
+    let r = resolve(&ebps, |p, q| {
+      match (p as usize, q as usize) {
+      (2, 3) => (1.0, 0),
+      (1, 2) => (2.0, 0),
+      _ => (3.0, 0)
+      }
+    });
+    assert_eq!(r, vec![ebp(2, 3, "tetra"), ebp(1, 4, "tetra")]);
+  }
+
+  #[test]
+  fn resolve_same_distance_by_step() {
+    let ebps = vec![ebp(1, 2, "cube"), ebp(1, 3, "cube")];
+    let r = resolve(&ebps, |_, q| (1.0, if q as usize == 3 { 5 } else { 9 }));
+    assert_eq!(r, vec![ebp(1, 3, "cube")]);
+  }
+
+  #[test]
+  fn clusters_grouping() {
+    // 1-2 and 3-4 are joined by 2-3, 5-6 is apart
+    let ebps = vec![ebp(1, 2, "octa"), ebp(3, 4, "octa"),
+      ebp(5, 6, "cone"), ebp(2, 3, "octa"), ebp(4, 1, "octa")];
+    let mut gs = clusters(&ebps);
+    for (g, _) in gs.iter_mut() { g.sort_by_key(|&o| o as usize); }
+    assert_eq!(gs, vec![
+      (vec![b(1), b(2), b(3), b(4)], "octa".to_string()),
+      (vec![b(5), b(6)], "cone".to_string())]);
+  }
+}
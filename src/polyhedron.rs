@@ -0,0 +1,421 @@
+/*
+  polyhedron.rs for c60
+
+  polyhedron catalogue (PE) and spawning on the TriMeshManager sequence
+*/
+
+use ph_faces::prec_eq_f;
+use trimesh::polyhedron::{
+  self, Polyhedron, tetra::*, cube::*, octa::*,
+  sphere::*, cylinder::*, capsule::*, cone::*,
+  torus::*, pipe::*, // polyhedron::pin
+  revolution::*,
+  Icosahedron,
+  {Dodecahedron, DodecahedronCenter},
+  {C60, C60Center}};
+use trimesh::tmm::*;
+
+use anyslot::anyslot::*;
+
+use ode_rs::ode::*;
+
+use crate::game::SimApp;
+use crate::registry::*;
+
+/// mesh type (name given to tms!) and setup parameters
+#[derive(Debug, Clone, Copy)]
+pub enum Mesh {
+  /// r
+  MTetra(dReal),
+  /// r
+  MCube(dReal),
+  /// r
+  MCubeCenter(dReal),
+  /// r
+  MOcta(dReal),
+  /// r, n
+  MRSphere(dReal, usize),
+  /// r, l, n
+  MCylinder(dReal, dReal, usize),
+  /// r, l, n
+  MCapsule(dReal, dReal, usize),
+  /// r, l, n
+  MCone(dReal, dReal, usize),
+  /// r0, r1, n, m
+  MTorus(dReal, dReal, usize, usize),
+  /// r0, r1, n, m
+  MRTorus(dReal, dReal, usize, usize),
+  /// r0, r1, l, n, m
+  MRing(dReal, dReal, dReal, usize, usize),
+  /// r0, r1, l, n
+  MTube(dReal, dReal, dReal, usize),
+  /// a, r0, r1, l, n
+  MHalfPipe(dReal, dReal, dReal, dReal, usize),
+  /// r, n, m
+  MPin(dReal, usize, usize),
+  /// r, n, m, (cap, cap) (linear profile)
+  MRevolution(dReal, usize, usize, (bool, bool)),
+  /// r, n, m, (cap, cap), profile table
+  MRevolutionTbl(dReal, usize, usize, (bool, bool), &'static [(dReal, dReal)]),
+  /// r
+  MIcosahedron(dReal),
+  /// r
+  MDodecahedron(dReal),
+  /// r
+  MDodecahedronCenter(dReal),
+  /// r
+  MC60(dReal),
+  /// r
+  MC60Center(dReal)
+}
+pub use Mesh::*;
+
+impl Mesh {
+  /// mesh key (name given to tms!)
+  pub fn key(&self) -> &'static str {
+    match self {
+    MTetra(..) => "tetra",
+    MCube(..) => "cube",
+    MCubeCenter(..) => "cube_center",
+    MOcta(..) => "octa",
+    MRSphere(..) => "r_sphere",
+    MCylinder(..) => "cylinder",
+    MCapsule(..) => "capsule",
+    MCone(..) => "cone",
+    MTorus(..) => "torus",
+    MRTorus(..) => "r_torus",
+    MRing(..) => "ring",
+    MTube(..) => "tube",
+    MHalfPipe(..) => "half_pipe",
+    MPin(..) => "pin",
+    MRevolution(..) | MRevolutionTbl(..) => "revolution",
+    MIcosahedron(..) => "icosahedron",
+    MDodecahedron(..) => "dodecahedron",
+    MDodecahedronCenter(..) => "dodecahedron_center",
+    MC60(..) => "c60",
+    MC60Center(..) => "c60_center"
+    }
+  }
+}
+
+/// default orientation
+#[derive(Debug, Clone, Copy)]
+pub enum Orient {
+  /// identity
+  OI,
+  /// axis and angle
+  OAxis([dReal; 3], dReal),
+  /// dMatrix3 euler angles phi=-x, theta=-y, psi=-z
+  OEuler(dReal, dReal, dReal)
+}
+pub use Orient::*;
+
+impl Orient {
+  /// quaternion
+  pub fn q(&self) -> dQuaternion {
+    match *self {
+    OI => QI,
+    OAxis(a, t) => dQuaternion::from_axis_and_angle(a, t),
+    OEuler(p, t, s) => dQuaternion::from_R(dMatrix3::from_euler_angles(p, t, s))
+    }
+  }
+}
+
+/// catalogue entry
+#[derive(Debug, Clone, Copy)]
+pub struct PEDef {
+  /// name (used by config files)
+  pub name: &'static str,
+  /// mesh type and setup parameters
+  pub mesh: Mesh,
+  /// index on the mesh key (N0 N1)
+  pub id: usize,
+  /// texture mode (true: on the one texture, false: texture each face)
+  pub tf: bool,
+  /// default orientation
+  pub o: Orient,
+  /// colour
+  pub col: dVector4
+}
+
+/// revolution profile ("ERevolutionN1")
+const REV_TBL: [(dReal, dReal); 5] = [
+  (0.0, 1.0), (0.2, 1.0), (0.4, 1.0), (0.6, 1.0), (0.8, 1.0)];
+
+/// +Y
+const OY: Orient = OI;
+/// +Z
+const OZ: Orient = OAxis([1.0, 0.0, 0.0], PIh);
+/// +Z
+const OZE: Orient = OEuler(-PIh, 0.0, 0.0);
+/// +YZX
+const OYZX: Orient = OEuler(0.0, -PIh, 0.0);
+/// -X
+const ONX: Orient = OEuler(0.0, 0.0, -PIh);
+
+/// polyhedron catalogue (PE is the index, one entry per polyhedron)
+pub const CATALOGUE: &[PEDef] = &[
+  PEDef{name: "ETetra", mesh: MTetra(1.0), id: 0, tf: false,
+    o: OY, col: [0.8, 0.6, 0.2, 0.8]},
+  PEDef{name: "ECube", mesh: MCube(0.2), id: 0, tf: false,
+    o: OZ, col: [0.2, 0.8, 0.6, 0.8]},
+  PEDef{name: "ECubeCenter", mesh: MCubeCenter(0.2), id: 0, tf: false,
+    o: OZE, col: [0.6, 0.2, 0.8, 0.8]},
+  PEDef{name: "EOcta", mesh: MOcta(1.0), id: 0, tf: false,
+    o: OZ, col: [0.8, 0.8, 0.2, 0.8]},
+  PEDef{name: "ERSphere", mesh: MRSphere(0.2, 6), id: 0, tf: false,
+    o: OZ, col: [0.8, 0.2, 0.8, 0.8]},
+  PEDef{name: "ECylinder", mesh: MCylinder(0.2, 2.0, 6), id: 0, tf: false,
+    o: OZ, col: [0.2, 0.8, 0.8, 0.8]},
+  PEDef{name: "ECapsule", mesh: MCapsule(0.2, 2.0, 6), id: 0, tf: false,
+    o: OZ, col: [0.8, 0.2, 0.6, 0.8]},
+  PEDef{name: "ECone", mesh: MCone(0.2, 2.0, 6), id: 0, tf: false,
+    o: OZ, col: [0.6, 0.8, 0.2, 0.8]},
+  PEDef{name: "ETorus", mesh: MTorus(2.0, 0.5, 6, 6), id: 0, tf: false,
+    o: OY, col: [0.2, 0.6, 0.8, 0.8]},
+  PEDef{name: "ERTorus", mesh: MRTorus(2.0, 0.5, 12, 6), id: 0, tf: false,
+    o: ONX, col: [0.8, 0.6, 0.2, 0.8]},
+  PEDef{name: "ERing", mesh: MRing(2.0, 0.1, 0.4, 12, 6), id: 0, tf: false,
+    o: OZ, col: [0.2, 0.8, 0.6, 0.8]},
+  PEDef{name: "ETube", mesh: MTube(3.0, 2.8, 4.0, 6), id: 0, tf: false,
+    o: OZ, col: [0.6, 0.2, 0.8, 0.8]},
+  PEDef{name: "EHalfPipe", mesh: MHalfPipe(PI, 3.0, 2.8, 4.0, 6),
+    id: 0, tf: false, o: OYZX, col: [0.8, 0.8, 0.2, 0.8]},
+  PEDef{name: "EPin", mesh: MPin(0.2, 8, 6), id: 0, tf: false,
+    o: OZ, col: [0.8, 0.2, 0.8, 0.8]},
+  PEDef{name: "ERevolutionN0", mesh: MRevolution(1.0, 2, 6, (true, true)),
+    id: 0, tf: false, o: OZE, col: [0.2, 0.8, 0.8, 0.8]},
+  PEDef{name: "ERevolutionN1",
+    mesh: MRevolutionTbl(1.0, 2, 6, (true, true), &REV_TBL),
+    id: 1, tf: false, o: ONX, col: [0.8, 0.2, 0.6, 0.8]},
+  PEDef{name: "EIcosahedronN0", mesh: MIcosahedron(0.2), id: 0, tf: true,
+    o: OZ, col: [0.6, 0.8, 0.2, 0.8]},
+  PEDef{name: "EIcosahedronN1", mesh: MIcosahedron(0.2), id: 1, tf: false,
+    o: OZ, col: [0.2, 0.6, 0.8, 0.8]},
+  PEDef{name: "EDodecahedronN0", mesh: MDodecahedron(0.2), id: 0, tf: true,
+    o: OZ, col: [0.8, 0.6, 0.2, 0.8]},
+  PEDef{name: "EDodecahedronN1", mesh: MDodecahedron(0.2), id: 1, tf: false,
+    o: OZ, col: [0.2, 0.8, 0.6, 0.8]},
+  PEDef{name: "EDodecahedronCenterN0", mesh: MDodecahedronCenter(0.2),
+    id: 0, tf: true, o: OZ, col: [0.6, 0.2, 0.8, 0.8]},
+  PEDef{name: "EDodecahedronCenterN1", mesh: MDodecahedronCenter(0.2),
+    id: 1, tf: false, o: OZ, col: [0.8, 0.8, 0.2, 0.8]},
+  PEDef{name: "EC60N0", mesh: MC60(0.2), id: 0, tf: true,
+    o: OZ, col: [0.8, 0.2, 0.8, 0.8]},
+  PEDef{name: "EC60N1", mesh: MC60(0.2), id: 1, tf: false,
+    o: OZ, col: [0.2, 0.8, 0.8, 0.8]},
+  PEDef{name: "EC60CenterN0", mesh: MC60Center(0.2), id: 0, tf: true,
+    o: OZ, col: [0.8, 0.2, 0.6, 0.8]},
+  PEDef{name: "EC60CenterN1", mesh: MC60Center(0.2), id: 1, tf: false,
+    o: OZ, col: [0.6, 0.8, 0.2, 0.8]}];
+
+/// polyhedron kind (index into CATALOGUE)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PE(pub usize);
+
+impl PE {
+  /// number of catalogue entries
+  pub const COUNT: usize = CATALOGUE.len();
+
+  /// from catalogue index (None when out of the catalogue)
+  pub fn from_usize(u: usize) -> Option<Self> {
+    if u >= PE::COUNT { return None; }
+    Some(PE(u))
+  }
+
+  /// catalogue entry
+  pub fn def(&self) -> &'static PEDef {
+    &CATALOGUE[self.0]
+  }
+
+  /// from catalogue name (for example "ETetra")
+  pub fn from_name(s: &str) -> Option<Self> {
+    CATALOGUE.iter().position(|d| d.name == s).map(PE)
+  }
+
+  /// true when the mesh key is given to tms! by setup_polyhedron_sequence
+  pub fn is_key(k: &str) -> bool {
+    CATALOGUE.iter().any(|d| d.mesh.key() == k)
+  }
+
+  /// mesh key (name given to tms!) shared by N0 and N1
+  pub fn key(&self) -> &'static str {
+    self.def().mesh.key()
+  }
+}
+
+/// create the trimesh of (key, polyhedron) at pos q (local to this module)
+macro_rules! cp {
+  ($slf: expr, $col: expr, $pos: expr, $q: expr, $sph: expr) => {{
+    let (s, ph) = $sph;
+    let d = 1e-2;
+    let dm = if prec_eq_f(ph.vol, 1e-6, 0.0) { d } else { d / ph.vol };
+    let krp = Krp::new(false, false, false, 0.2, 0.3); // set true later
+    let mi_tm = MetaTriMesh::new(false, dm, &mut ph.tmv, krp, 0, $col);
+    let k = $slf.ts(s.as_str());
+    let (body, _, _) = $slf.super_mut().creator(k.as_str(), mi_tm);
+    $slf.set_pos_Q(body, $pos, $q);
+    k
+  }}
+}
+
+/// tms! key and polyhedron of the catalogue entry (setup: tms! it first)
+pub fn mesh_ph<'a>(tm: &'a mut TriMeshManager<f64>, d: &PEDef, setup: bool) ->
+  (String, &'a mut Polyhedron<f64>) {
+  let (i, tf) = (d.id, d.tf);
+  macro_rules! ph {
+    ($hm: ident, $t: ty, |$o: ident| $s: expr) => {{
+      if setup { let $o = tms!(tm, $hm, $t, i); $s; }
+      let (k, t) = tmg!(tm, $hm, i);
+      (k, &mut t.ph)
+    }}
+  }
+  match d.mesh {
+  MTetra(r) => ph!(tetra, Tetra::<f64>, |o| o.setup(r, tf)),
+  MCube(r) => ph!(cube, Cube::<f64>, |o| o.setup(r, tf)),
+  MCubeCenter(r) => ph!(cube_center, CubeCenter::<f64>, |o| o.setup(r, tf)),
+  MOcta(r) => ph!(octa, Octa::<f64>, |o| o.setup(r, tf)),
+  MRSphere(r, n) => ph!(r_sphere, RSphere::<f64>,
+    |o| o.setup(r, n as _, tf)),
+  MCylinder(r, l, n) => ph!(cylinder, Cylinder::<f64>,
+    |o| o.setup(r, l, n as _, tf)),
+  MCapsule(r, l, n) => ph!(capsule, Capsule::<f64>,
+    |o| o.setup(r, l, n as _, tf)),
+  MCone(r, l, n) => ph!(cone, Cone::<f64>, |o| o.setup(r, l, n as _, tf)),
+  MTorus(r0, r1, n, m) => ph!(torus, Torus::<f64>,
+    |o| o.setup(r0, r1, n as _, m as _, tf)),
+  MRTorus(r0, r1, n, m) => ph!(r_torus, RTorus::<f64>,
+    |o| o.setup(r0, r1, n as _, m as _, tf)),
+  MRing(r0, r1, l, n, m) => ph!(ring, Ring::<f64>,
+    |o| o.setup(r0, r1, l, n as _, m as _, tf)),
+  MTube(r0, r1, l, n) => ph!(tube, Tube::<f64>,
+    |o| o.setup(r0, r1, l, n as _, tf)),
+  MHalfPipe(a, r0, r1, l, n) => ph!(half_pipe, HalfPipe::<f64>,
+    |o| o.setup(a, r0, r1, l, n as _, tf)),
+  MPin(r, n, m) => ph!(pin, polyhedron::pin::Pin::<f64>,
+    |o| o.setup(r, n as _, m as _, tf)),
+  MRevolution(r, n, m, c) => ph!(revolution, Revolution::<f64>,
+    |o| o.setup(r, n as _, m as _, c, |n, m| {
+      (n as f64 / (m - 1) as f64, 1.0) }, tf)),
+  MRevolutionTbl(r, n, m, c, tbl) => ph!(revolution, Revolution::<f64>,
+    |o| o.setup_from_tbl(r, n as _, m as _, c, &tbl.to_vec(), tf)),
+  MIcosahedron(r) => ph!(icosahedron, Icosahedron::<f64>,
+    |o| o.setup(r, tf)),
+  MDodecahedron(r) => ph!(dodecahedron, Dodecahedron::<f64>,
+    |o| o.setup(r, tf)),
+  MDodecahedronCenter(r) => ph!(dodecahedron_center,
+    DodecahedronCenter::<f64>, |o| o.setup(r, tf)),
+  MC60(r) => ph!(c60, C60::<f64>, |o| o.setup(r, tf)),
+  MC60Center(r) => ph!(c60_center, C60Center::<f64>, |o| o.setup(r, tf))
+  }
+}
+
+/// setup polyhedron sequence on TriMeshManager (every CATALOGUE entry)
+pub fn setup_polyhedron_sequence() {
+  any_pinned_with_bg_mut!(TriMeshManager<f64>, 0, |tm| {
+    for d in CATALOGUE.iter() { mesh_ph(tm, d, true); }
+  });
+}
+
+impl SimApp {
+
+/// create c60 icosahedron
+pub fn create_c60_icosahedron(&mut self) {
+  for i in 0..2 {
+    let pe = PE::from_name(&format!("EIcosahedronN{}", i));
+    let d = pe.expect("catalogue").def();
+    any_pinned_with_bg_mut!(TriMeshManager<f64>, 0, |tm| {
+      let nk = cp!(self,
+        [0.8, 0.6, 0.2, 0.8],
+        [-4.0 + 2.0 * i as f64, 0.0, 2.0, 1.0],
+        QI,
+        mesh_ph(tm, d, false));
+      self.kgc(&nk);
+      self.register_key(&nk, pe, RTest);
+    });
+  }
+}
+
+/// create c60 dodecahedron
+pub fn create_c60_dodecahedron(&mut self) {
+  for i in 0..2 {
+    let pe = PE::from_name(&format!("EDodecahedronN{}", i));
+    let d = pe.expect("catalogue").def();
+    any_pinned_with_bg_mut!(TriMeshManager<f64>, 0, |tm| {
+      let nk = cp!(self,
+        [0.8, 0.6, 0.2, 0.8],
+        [-4.0 + 2.0 * i as f64, -4.0, 2.0, 1.0],
+        QI,
+        mesh_ph(tm, d, false));
+      self.kgc(&nk);
+      self.register_key(&nk, pe, RTest);
+    });
+  }
+}
+
+/// create c60 dodecahedron center
+pub fn create_c60_dodecahedron_center(&mut self) {
+  for i in 0..2 {
+    let pe = PE::from_name(&format!("EDodecahedronCenterN{}", i));
+    let d = pe.expect("catalogue").def();
+    any_pinned_with_bg_mut!(TriMeshManager<f64>, 0, |tm| {
+      let nk = cp!(self,
+        [0.8, 0.6, 0.2, 0.8],
+        [-4.0 + 2.0 * i as f64, -2.0, 2.0, 1.0],
+        QI,
+        mesh_ph(tm, d, false));
+      self.kgc(&nk);
+      self.register_key(&nk, pe, RTest);
+    });
+  }
+}
+
+/// create c60 fullerene
+pub fn create_c60_fullerene(&mut self) {
+  for i in 0..2 {
+    let pe = PE::from_name(&format!("EC60N{}", i));
+    let d = pe.expect("catalogue").def();
+    any_pinned_with_bg_mut!(TriMeshManager<f64>, 0, |tm| {
+      let nk = cp!(self,
+        [0.8, 0.6, 0.2, 0.8],
+        [-4.0 + 2.0 * i as f64, 4.0, 2.0, 1.0],
+        QI,
+        mesh_ph(tm, d, false));
+      self.kgc(&nk);
+      self.register_key(&nk, pe, RTest);
+    });
+  }
+}
+
+/// create c60 fullerene center
+pub fn create_c60_fullerene_center(&mut self) {
+  for i in 0..2 {
+    let pe = PE::from_name(&format!("EC60CenterN{}", i));
+    let d = pe.expect("catalogue").def();
+    any_pinned_with_bg_mut!(TriMeshManager<f64>, 0, |tm| {
+      let nk = cp!(self,
+        [0.8, 0.6, 0.2, 0.8],
+        [-4.0 + 2.0 * i as f64, 2.0, 2.0, 1.0],
+        QI,
+        mesh_ph(tm, d, false));
+      self.kgc(&nk);
+      self.register_key(&nk, pe, RTest);
+    });
+  }
+}
+
+/// create polyhedron (i is the index into CATALOGUE)
+pub fn create_polyhedron(&mut self, i: usize, p: dVector3) -> String {
+  let pe = PE(i % self.n);
+  let d = pe.def();
+  let (c, q) = (d.col, d.o.q());
+  let mut k: String = "".to_string(); // result
+  any_pinned_with_bg_mut!(TriMeshManager<f64>, 0, |tm| {
+    k = cp!(self, c, p, q, mesh_ph(tm, d, false));
+    println!("polyhedron: {}", k);
+  });
+  self.register_key(&k, Some(pe), RPiece);
+  k
+}
+
+}
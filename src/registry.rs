@@ -0,0 +1,172 @@
+/*
+  registry.rs for c60
+
+  typed object registry (dBodyID to kind tier step role)
+*/
+
+use ode_rs::ode::*;
+
+use std::collections::HashMap;
+
+use crate::polyhedron::PE;
+use crate::scenefile::ObjDef;
+
+/// role of the registered object
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[repr(usize)]
+pub enum Role {
+  /// game piece (preview, current, released and merged)
+  RPiece,
+  /// fixed frame (box, capsule, cylinder, slope, plane)
+  RFixture,
+  /// test object
+  RTest
+}
+pub use Role::*;
+
+/// category bit of game pieces
+pub const CAT_PIECE: u32 = 0x0001;
+/// category bit of fixed frames
+pub const CAT_FIXTURE: u32 = 0x0002;
+/// category bit of test objects
+pub const CAT_TEST: u32 = 0x0004;
+
+impl Role {
+  /// name used by files
+  pub fn name(&self) -> &'static str {
+    match self {
+    RPiece => "piece",
+    RFixture => "fixture",
+    RTest => "test"
+    }
+  }
+
+  /// role from name (None when unknown)
+  pub fn from_name(s: &str) -> Option<Role> {
+    [RPiece, RFixture, RTest].into_iter().find(|r| r.name() == s)
+  }
+
+  /// default category bits
+  pub fn cat(&self) -> u32 {
+    match self {
+    RPiece => CAT_PIECE,
+    RFixture => CAT_FIXTURE,
+    RTest => CAT_TEST
+    }
+  }
+
+  /// default merge bits (categories this role can merge with)
+  pub fn merge(&self) -> u32 {
+    match self {
+    RPiece => CAT_PIECE | CAT_TEST,
+    RFixture => 0,
+    RTest => CAT_PIECE | CAT_TEST
+    }
+  }
+}
+
+/// registered object
+#[derive(Debug, Clone)]
+pub struct Entry {
+  /// polyhedron kind (None when not in the catalogue)
+  pub pe: Option<PE>,
+  /// tier in the evolution chain (None when not in the chain)
+  pub tier: Option<usize>,
+  /// simulation step created on
+  pub step: usize,
+  /// role
+  pub role: Role,
+  /// category bits
+  pub cat: u32,
+  /// merge bits (merge when each cat matches the other merge bits)
+  pub merge: u32,
+  /// chain counter of the merge that spawned this (None: not by a merge)
+  pub chain: Option<usize>,
+  /// definition to build again (None: catalogue polyhedron)
+  pub def: Option<ObjDef>
+}
+
+impl Entry {
+  /// construct with the default category and merge bits of role
+  pub fn new(pe: Option<PE>, tier: Option<usize>, step: usize, role: Role)
+    -> Self {
+    Entry{pe, tier, step, role, cat: role.cat(), merge: role.merge(),
+      chain: None, def: None}
+  }
+
+  /// true when this can merge with the other (same kind is not checked)
+  pub fn is_mergeable(&self, o: &Entry) -> bool {
+    self.cat & o.merge != 0 && o.cat & self.merge != 0
+  }
+}
+
+/// registry &lt;dBodyID, Entry&gt;
+#[derive(Debug, Default)]
+pub struct Registry {
+  m: HashMap<dBodyID, Entry>
+}
+
+impl Registry {
+  /// register (overwrite when exists)
+  pub fn insert(&mut self, id: dBodyID, e: Entry) {
+    self.m.insert(id, e);
+  }
+
+  /// unregister (must be called before the body is destroyed)
+  pub fn remove(&mut self, id: dBodyID) -> Option<Entry> {
+    self.m.remove(&id)
+  }
+
+  /// entry of id
+  pub fn get(&self, id: dBodyID) -> Option<&Entry> {
+    self.m.get(&id)
+  }
+
+  /// set category and merge bits
+  pub fn set_bits(&mut self, id: dBodyID, cat: u32, merge: u32) {
+    let Some(e) = self.m.get_mut(&id) else { return; };
+    e.cat = cat;
+    e.merge = merge;
+  }
+
+  /// set chain counter of the merge that spawned id
+  pub fn set_chain(&mut self, id: dBodyID, chain: usize) {
+    let Some(e) = self.m.get_mut(&id) else { return; };
+    e.chain = Some(chain);
+  }
+
+  /// set definition to build id again
+  pub fn set_def(&mut self, id: dBodyID, def: ObjDef) {
+    let Some(e) = self.m.get_mut(&id) else { return; };
+    e.def = Some(def);
+  }
+
+  /// mesh key when both are mergeable polyhedra of the same kind (O(1))
+  pub fn mergeable(&self, a: dBodyID, b: dBodyID) -> Option<&'static str> {
+    let (ea, eb) = (self.get(a)?, self.get(b)?);
+    if !ea.is_mergeable(eb) { return None; }
+    let (pa, pb) = (ea.pe?, eb.pe?);
+    if pa.key() != pb.key() { return None; }
+    Some(pa.key())
+  }
+
+  /// all entries (unordered)
+  pub fn iter(&self) -> impl Iterator<Item=(&dBodyID, &Entry)> {
+    self.m.iter()
+  }
+
+  /// number of registered objects
+  pub fn len(&self) -> usize {
+    self.m.len()
+  }
+
+  /// true when no object is registered
+  pub fn is_empty(&self) -> bool {
+    self.m.is_empty()
+  }
+
+  /// clear all
+  pub fn clear(&mut self) {
+    self.m.clear();
+  }
+}
@@ -0,0 +1,272 @@
+/*
+  replay.rs for c60
+
+  command recording and playback (session header and step cmd)
+*/
+
+use std::fs;
+use std::io::Write;
+use std::collections::HashMap;
+
+use ode_rs::ode::dReal;
+
+use crate::config::{Evolution, Arena};
+use crate::scenes::Scene;
+use crate::merge::Spawn;
+use crate::polyhedron::PE;
+
+/// session options written in the header (None: not in the header)
+#[derive(Debug, Clone, Default)]
+pub struct Session {
+  /// scene (applied by play_replay)
+  pub scene: Option<Scene>,
+  /// spawn mode (applied)
+  pub spawn: Option<Spawn>,
+  /// cluster merge (applied)
+  pub cluster: Option<bool>,
+  /// arena (applied)
+  pub arena: Option<Arena>,
+  /// evolution chain and drop pool (applied)
+  pub evo: Option<Evolution>,
+  /// undo depth (checked by play_replay)
+  pub undo: Option<usize>,
+  /// combo window steps (checked)
+  pub combo: Option<usize>,
+  /// height limit line (z None: arena wall height, seconds) (checked)
+  pub limit: Option<(Option<dReal>, dReal)>,
+  /// hash of the scene file (Some(None): no scene file) (checked)
+  pub scene_file: Option<Option<u64>>
+}
+
+/// commands recorded with the step they arrived on, and commands to play back
+#[derive(Debug, Default)]
+pub struct Replay {
+  /// seed of the recorded session
+  pub seed: u64,
+  /// session options of the recorded session
+  pub session: Session,
+  /// recorded commands (step, cmd)
+  pub rec: Vec<(usize, i32)>,
+  /// commands to play back (step, cmd)
+  pub play: Vec<(usize, i32)>,
+  /// next index of play
+  pos: usize,
+  /// path written by start and record (None: not written)
+  path: Option<String>,
+  /// file opened by start (every record is appended)
+  file: Option<fs::File>
+}
+
+impl Replay {
+  /// construct for the session of seed
+  pub fn new(seed: u64) -> Self {
+    Replay{seed, ..Default::default()}
+  }
+
+  /// record to path (opened by start)
+  pub fn record_to(&mut self, path: &str) {
+    self.path = Some(path.to_string());
+  }
+
+  /// session options written in the header
+  pub fn set_session(&mut self, session: Session) {
+    self.session = session;
+  }
+
+  /// create the file of record_to and write the header and rec so far
+  /// (once, later calls keep the file open)
+  pub fn start(&mut self) -> Result<(), String> {
+    let Some(path) = &self.path else { return Ok(()); };
+    if self.file.is_some() { return Ok(()); }
+    let mut f = fs::File::create(path).map_err(|e| format!("{}: {}", path, e))?;
+    f.write_all(self.to_text().as_bytes())
+      .map_err(|e| format!("{}: {}", path, e))?;
+    self.file = Some(f);
+    Ok(())
+  }
+
+  /// record command arrived on step (appended to the file at once)
+  pub fn record(&mut self, step: usize, cmd: i32) {
+    self.rec.push((step, cmd));
+    let Some(f) = &mut self.file else { return; };
+    if let Err(e) = writeln!(f, "{} {}", step, cmd).and_then(|_| f.flush()) {
+      println!("{}: {}", self.path.as_deref().unwrap_or(""), e);
+      self.file = None; // stop writing, rec is kept
+    }
+  }
+
+  /// commands to feed back on step
+  pub fn take(&mut self, step: usize) -> Vec<i32> {
+    let mut cmds = vec![];
+    while self.pos < self.play.len() && self.play[self.pos].0 <= step {
+      cmds.push(self.play[self.pos].1);
+      self.pos += 1;
+    }
+    cmds
+  }
+
+  /// true while commands remain to play back
+  pub fn is_playing(&self) -> bool {
+    self.pos < self.play.len()
+  }
+
+  /// header (seed and session options) and rec as text
+  pub fn to_text(&self) -> String {
+    let ss = &self.session;
+    let mut s = format!("# c60 replay\nseed {}\n", self.seed);
+    if let Some(sc) = ss.scene { s.push_str(&format!("scene {}\n", sc.name())); }
+    if let Some(sp) = ss.spawn { s.push_str(&format!("spawn {}\n", sp.name())); }
+    if let Some(c) = ss.cluster { s.push_str(&format!("cluster {}\n", c)); }
+    if let Some(a) = &ss.arena {
+      s.push_str(&format!("arena {} {} {} {} {} {} {} {}\n",
+        a.size[0], a.size[1], a.size[2], a.thick, a.center[0], a.center[1],
+        a.drop, a.radius));
+    }
+    if let Some(e) = &ss.evo {
+      let ped: Vec<&str> = e.ped.iter().map(|pe| pe.def().name).collect();
+      s.push_str(&format!("ped {}\n", ped.join(" ")));
+      let mut ks: Vec<&String> = e.evo.keys().collect();
+      ks.sort();
+      for k in ks {
+        s.push_str(&format!("evo {} {}\n", k, e.evo[k].def().name));
+      }
+    }
+    if let Some(u) = ss.undo { s.push_str(&format!("undo {}\n", u)); }
+    if let Some(c) = ss.combo { s.push_str(&format!("combo {}\n", c)); }
+    if let Some((z, t)) = ss.limit {
+      match z {
+      Some(z) => s.push_str(&format!("limit {} {}\n", z, t)),
+      None => s.push_str(&format!("limit arena {}\n", t))
+      }
+    }
+    if let Some(h) = ss.scene_file {
+      match h {
+      Some(h) => s.push_str(&format!("scene_file {:016x}\n", h)),
+      None => s.push_str("scene_file none\n")
+      }
+    }
+    for (step, cmd) in self.rec.iter() {
+      s.push_str(&format!("{} {}\n", step, cmd));
+    }
+    s
+  }
+
+  /// load play (and header) from path
+  pub fn load(path: &str) -> Result<Self, String> {
+    let s = fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+    Replay::from_text(&s).map_err(|e| format!("{}:{}", path, e))
+  }
+
+  /// parse play (and header) from text (error is line: content)
+  pub fn from_text(s: &str) -> Result<Self, String> {
+    let mut r = Replay::default();
+    let ss = &mut r.session;
+    let (mut ped, mut evo): (Vec<PE>, HashMap<String, PE>) = (vec![], HashMap::new());
+    for (n, l) in s.lines().enumerate() {
+      let l = l.trim();
+      if l.is_empty() || l.starts_with('#') { continue; }
+      let err = || format!("{}: {}", n + 1, l);
+      let v: Vec<&str> = l.split_whitespace().collect();
+      match v[0] {
+      "seed" if v.len() == 2 => {
+        r.seed = v[1].parse::<u64>().map_err(|_| err())?; },
+      "scene" if v.len() == 2 => {
+        ss.scene = Some(Scene::from_name(v[1]).ok_or_else(err)?); },
+      "spawn" if v.len() == 2 => {
+        ss.spawn = Some(Spawn::from_name(v[1]).ok_or_else(err)?); },
+      "cluster" if v.len() == 2 => {
+        ss.cluster = Some(v[1].parse::<bool>().map_err(|_| err())?); },
+      "arena" if v.len() == 9 => {
+        let f = v[1..].iter().map(|a| a.parse::<f64>())
+          .collect::<Result<Vec<_>, _>>().map_err(|_| err())?;
+        let a = Arena{size: [f[0], f[1], f[2]], thick: f[3],
+          center: [f[4], f[5]], drop: f[6], radius: f[7]};
+        a.check().map_err(|e| format!("{} ({})", err(), e))?;
+        ss.arena = Some(a);
+      },
+      "ped" if v.len() >= 2 => {
+        for name in v[1..].iter() { ped.push(PE::from_name(name).ok_or_else(err)?); }
+      },
+      "evo" if v.len() == 3 => {
+        if !PE::is_key(v[1]) { return Err(err()); }
+        evo.insert(v[1].to_string(), PE::from_name(v[2]).ok_or_else(err)?);
+      },
+      "undo" if v.len() == 2 => {
+        ss.undo = Some(v[1].parse::<usize>().map_err(|_| err())?); },
+      "combo" if v.len() == 2 => {
+        ss.combo = Some(v[1].parse::<usize>().map_err(|_| err())?); },
+      "limit" if v.len() == 3 => {
+        let z = match v[1] {
+        "arena" => None,
+        z => Some(z.parse::<dReal>().map_err(|_| err())?)
+        };
+        ss.limit = Some((z, v[2].parse::<dReal>().map_err(|_| err())?));
+      },
+      "scene_file" if v.len() == 2 => {
+        ss.scene_file = Some(match v[1] {
+        "none" => None,
+        h => Some(u64::from_str_radix(h, 16).map_err(|_| err())?)
+        });
+      },
+      _ if v.len() == 2 => {
+        let step = v[0].parse::<usize>().map_err(|_| err())?;
+        let cmd = v[1].parse::<i32>().map_err(|_| err())?;
+        r.play.push((step, cmd));
+      },
+      _ => { return Err(err()); }
+      }
+    }
+    if !ped.is_empty() { ss.evo = Some(Evolution{ped, evo}); }
+    else if !evo.is_empty() {
+      return Err(format!("{}: evo without ped", s.lines().count()));
+    }
+    Ok(r)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::scenes::SSandbox;
+  use crate::merge::SCalm;
+
+  #[test]
+  fn record_load_round_trip() {
+    let path = std::env::temp_dir().join(format!("c60_replay_{}.txt",
+      std::process::id()));
+    let path = path.to_str().expect("temp path");
+    let mut r = Replay::new(42);
+    let a = Arena{size: [6.0, 7.5, 4.0], thick: 0.25, center: [1.0, -0.5],
+      drop: 1.5, radius: 0.8};
+    r.set_session(Session{scene: Some(SSandbox), spawn: Some(SCalm),
+      cluster: Some(true), arena: Some(a), evo: Some(Evolution::default()),
+      undo: Some(4), combo: Some(300), limit: Some((None, 2.5)),
+      scene_file: Some(Some(0x0123_4567_89ab_cdef))});
+    r.record(3, 'x' as i32); // before start is written by start
+    r.record_to(path);
+    r.start().expect("start");
+    r.record(120, ' ' as i32); // appended at once
+    let mut l = Replay::load(path).expect("load");
+    std::fs::remove_file(path).ok();
+    assert_eq!(l.seed, 42);
+    let ls = &l.session;
+    assert_eq!((ls.scene, ls.spawn, ls.cluster), (Some(SSandbox), Some(SCalm), Some(true)));
+    assert_eq!((ls.undo, ls.combo, ls.limit), (Some(4), Some(300), Some((None, 2.5))));
+    assert_eq!(ls.scene_file, Some(Some(0x0123_4567_89ab_cdef)));
+    l.rec = l.play.clone(); // same header and commands are written back
+    assert_eq!(l.to_text(), r.to_text());
+    assert_eq!(l.take(2), vec![]);
+    assert_eq!(l.take(200), vec!['x' as i32, ' ' as i32]);
+    assert!(!l.is_playing());
+  }
+
+  #[test]
+  fn load_rejects_bad_lines() {
+    assert!(Replay::from_text("seed 1\nscene nowhere\n").is_err());
+    assert!(Replay::from_text("arena 1 1 1 0 0 0 2 1\n").is_err());
+    assert!(Replay::from_text("evo tetra ECone\n").is_err());
+    assert!(Replay::from_text("limit high 3\n").is_err());
+    assert!(Replay::from_text("scene_file xyz\n").is_err());
+    assert!(Replay::from_text("12 x\n").is_err());
+  }
+}
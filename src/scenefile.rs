@@ -0,0 +1,443 @@
+/*
+  scenefile.rs for c60
+
+  declarative scene file (toml list of primitives composites and polyhedra)
+*/
+
+use serde::{Serialize, Deserialize};
+
+use ode_rs::ode::*;
+use ode_rs::ode::meta::MetaId;
+
+use std::fs;
+
+use crate::game::SimApp;
+use crate::polyhedron::PE;
+use crate::registry::*;
+
+/// shape of an object (kind = "box" ... "polyhedron" in the file)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind")]
+pub enum Prim {
+  /// MetaBox
+  #[serde(rename = "box")]
+  PBox{lxyz: [dReal; 3]},
+  /// MetaSphere
+  #[serde(rename = "sphere")]
+  PSphere{r: dReal},
+  /// MetaCapsule
+  #[serde(rename = "capsule")]
+  PCapsule{r: dReal, l: dReal},
+  /// MetaCylinder
+  #[serde(rename = "cylinder")]
+  PCylinder{r: dReal, l: dReal},
+  /// MetaPlane
+  #[serde(rename = "plane")]
+  PPlane{lxyz: [dReal; 3], norm: [dReal; 3]},
+  /// oyk built-in trimesh (tetra cube icosahedron bunny)
+  #[serde(rename = "trimesh")]
+  PTriMesh{mesh: String},
+  /// oyk built-in convex (tetra cube icosahedron bunny)
+  #[serde(rename = "convex")]
+  PConvex{mesh: String},
+  /// MetaComposite of parts (box sphere capsule cylinder trimesh convex)
+  #[serde(rename = "composite")]
+  PComposite{parts: Vec<Part>},
+  /// catalogue polyhedron (colour and mass from the catalogue)
+  #[serde(rename = "polyhedron")]
+  PPolyhedron{pe: String}
+}
+pub use Prim::*;
+
+/// contact parameters (preset name or Krp::new(k, g, c, p, m) arguments)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum KrpDef {
+  /// KRP001 KRP080 KRP095 KRP100 KRPnk
+  KPreset(String),
+  /// arguments of Krp::new
+  KParam{k: bool, g: bool, c: bool, p: dReal, m: dReal}
+}
+pub use KrpDef::*;
+
+impl KrpDef {
+  /// Krp (None when the preset is unknown)
+  pub fn krp(&self) -> Option<Krp> {
+    match self {
+    KPreset(s) => match s.as_str() {
+      "KRP001" => Some(KRP001),
+      "KRP080" => Some(KRP080),
+      "KRP095" => Some(KRP095),
+      "KRP100" => Some(KRP100),
+      "KRPnk" => Some(KRPnk),
+      _ => None
+      },
+    KParam{k, g, c, p, m} => Some(Krp::new(*k, *g, *c, *p, *m))
+    }
+  }
+}
+
+impl From<&Krp> for KrpDef {
+  fn from(krp: &Krp) -> Self {
+    KParam{k: krp.k, g: krp.g, c: krp.c, p: krp.bounce, m: krp.mu}
+  }
+}
+
+impl Default for KrpDef {
+  fn default() -> Self { KPreset("KRP095".to_string()) }
+}
+
+/// rotation about axis by deg degrees
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Rot {
+  /// axis
+  pub axis: [dReal; 3],
+  /// angle (degree)
+  pub deg: dReal
+}
+
+impl Default for Rot {
+  fn default() -> Self { Rot{axis: [0.0, 0.0, 1.0], deg: 0.0} }
+}
+
+impl Rot {
+  /// quaternion
+  pub fn q(&self) -> dQuaternion {
+    if self.deg == 0.0 { return QI; }
+    dQuaternion::from_axis_and_angle(self.axis, self.deg * PI / 180.0)
+  }
+
+  /// from quaternion (w x y z)
+  pub fn from_q(q: dQuaternion) -> Self {
+    let w = q[0].clamp(-1.0, 1.0);
+    let s = (1.0 - w * w).sqrt();
+    if s < 1e-12 { return Rot::default(); }
+    Rot{axis: [q[1] / s, q[2] / s, q[3] / s], deg: 2.0 * w.acos() * 180.0 / PI}
+  }
+}
+
+fn default_density() -> dReal { 0.1 }
+fn default_col() -> dVector4 { [0.8, 0.8, 0.8, 0.8] }
+fn default_role() -> String { "test".to_string() }
+
+/// part of a composite (offset and rotation from the composite centre)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Part {
+  /// shape
+  #[serde(flatten)]
+  pub prim: Prim,
+  /// offset
+  #[serde(default)]
+  pub offset: [dReal; 3],
+  /// rotation
+  #[serde(default)]
+  pub rot: Rot,
+  /// colour
+  #[serde(default = "default_col")]
+  pub col: dVector4,
+  /// contact parameters
+  #[serde(default)]
+  pub krp: KrpDef,
+  /// mass density (dm of the Meta, not the total mass)
+  #[serde(default = "default_density")]
+  pub density: dReal
+}
+
+/// object ([[object]] in the file)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ObjDef {
+  /// key (timestamped kind when omitted)
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub name: Option<String>,
+  /// shape
+  #[serde(flatten)]
+  pub prim: Prim,
+  /// position
+  pub pos: [dReal; 3],
+  /// rotation
+  #[serde(default)]
+  pub rot: Rot,
+  /// colour (not for polyhedron)
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub col: Option<dVector4>,
+  /// contact parameters (not for polyhedron)
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub krp: Option<KrpDef>,
+  /// mass density (dm of the Meta, not the total mass, not for polyhedron)
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub density: Option<dReal>,
+  /// density is the total mass (box sphere plane, as creator_m)
+  #[serde(default)]
+  pub total: bool,
+  /// role piece fixture test (default test)
+  #[serde(default = "default_role")]
+  pub role: String
+}
+
+impl ObjDef {
+  /// definition of a Meta created with key and role (pos and rot not set)
+  /// (None for trimesh and convex not built in)
+  pub fn from_meta(k: &str, mi: &dyn MetaInf, total: bool, role: Role)
+    -> Option<Self> {
+    let prim = match mi.id() {
+    MetaId::Composite => {
+      let mc = mi.as_composite();
+      let mut parts = vec![];
+      for (j, e) in mc.elems.iter().enumerate() {
+        let (prim, density) = prim_of(&**e)?;
+        let o = mc.ofs[j];
+        parts.push(Part{prim, offset: [o[0], o[1], o[2]],
+          rot: Rot::from_q(mc.qs[j]), col: e.get_tcm().col,
+          krp: e.get_krp().into(), density});
+      }
+      (PComposite{parts}, None)
+    },
+    _ => { let (prim, dm) = prim_of(mi)?; (prim, Some(dm)) }
+    };
+    Some(ObjDef{name: Some(k.to_string()), prim: prim.0,
+      pos: [0.0; 3], rot: Rot::default(),
+      col: Some(mi.get_tcm().col), krp: Some(mi.get_krp().into()),
+      density: prim.1, total, role: role.name().to_string()})
+  }
+
+  /// check preset role catalogue name and parts (as from_toml)
+  pub fn check(&self) -> Result<(), String> {
+    if self.krp().is_none() { return Err(format!("krp {:?}", self.krp)); }
+    if self.role().is_none() { return Err(format!("role {}", self.role)); }
+    match &self.prim {
+    PPolyhedron{pe} => {
+      if PE::from_name(pe).is_none() {
+        return Err(format!("unknown PE: {}", pe));
+      }
+      if self.col.is_some() || self.krp.is_some() || self.density.is_some() {
+        return Err("polyhedron takes col krp density from the catalogue"
+          .to_string());
+      }
+    },
+    PComposite{parts} => {
+      if parts.is_empty() { return Err("no parts".to_string()); }
+      for p in parts.iter() {
+        if p.krp.krp().is_none() { return Err(format!("krp {:?}", p.krp)); }
+        match &p.prim {
+        PBox{..} | PSphere{..} | PCapsule{..} | PCylinder{..} => {},
+        PTriMesh{mesh} | PConvex{mesh} => {
+          if !BUILTIN.contains(&mesh.as_str()) {
+            return Err(format!("unknown mesh: {}", mesh));
+          }
+        },
+        _ => { return Err(
+          "part must be box sphere capsule cylinder trimesh convex"
+          .to_string()); }
+        }
+      }
+    },
+    PTriMesh{mesh} | PConvex{mesh} if !BUILTIN.contains(&mesh.as_str()) => {
+      return Err(format!("unknown mesh: {}", mesh));
+    },
+    _ => {}
+    }
+    if self.total && !matches!(self.prim, PBox{..} | PSphere{..} | PPlane{..}) {
+      return Err("total is for box sphere plane".to_string());
+    }
+    Ok(())
+  }
+
+  /// role (None when unknown)
+  pub fn role(&self) -> Option<Role> {
+    Role::from_name(&self.role)
+  }
+
+  /// Krp (default KRP095, None when the preset is unknown)
+  pub fn krp(&self) -> Option<Krp> {
+    self.krp.clone().unwrap_or_default().krp()
+  }
+
+  /// colour (default grey)
+  pub fn col(&self) -> dVector4 {
+    self.col.unwrap_or_else(default_col)
+  }
+
+  /// mass density (default 0.1)
+  pub fn density(&self) -> dReal {
+    self.density.unwrap_or_else(default_density)
+  }
+}
+
+/// scene file
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SceneFile {
+  /// objects built in order
+  #[serde(default)]
+  pub object: Vec<ObjDef>
+}
+
+/// oyk built-in trimesh of the name
+fn builtin_tmv(mesh: &str) -> Option<*mut trimeshvi> {
+  Some(unsafe { match mesh {
+  "tetra" => &mut *tetra::tmv,
+  "cube" => &mut *cube::tmv,
+  "icosahedron" => &mut *icosahedron::tmv,
+  "bunny" => &mut *bunny::tmv,
+  _ => return None
+  } })
+}
+
+/// oyk built-in convex of the name
+fn builtin_fvp(mesh: &str) -> Option<*mut convexfvp> {
+  Some(unsafe { match mesh {
+  "tetra" => &mut *tetra::fvp,
+  "cube" => &mut *cube::fvp,
+  "icosahedron" => &mut *icosahedron::fvp,
+  "bunny" => &mut *bunny::fvp,
+  _ => return None
+  } })
+}
+
+/// names of the oyk built-in meshes
+const BUILTIN: [&str; 4] = ["tetra", "cube", "icosahedron", "bunny"];
+
+/// Meta of a primitive or part (None for composite and polyhedron)
+fn meta(prim: &Prim, dm: dReal, krp: Krp, col: dVector4)
+  -> Option<Box<dyn MetaInf>> {
+  Some(match prim {
+  PBox{lxyz} => MetaBox::new(dm, [lxyz[0], lxyz[1], lxyz[2], 0.0],
+    krp, 0, col),
+  PSphere{r} => MetaSphere::new(dm, *r, krp, 0, col),
+  PCapsule{r, l} => MetaCapsule::new(dm, *r, *l, krp, 0, col),
+  PCylinder{r, l} => MetaCylinder::new(dm, *r, *l, krp, 0, col),
+  PPlane{lxyz, norm} => MetaPlane::new(dm, [lxyz[0], lxyz[1], lxyz[2], 0.0],
+    [norm[0], norm[1], norm[2], 0.0], krp, 0, col),
+  PTriMesh{mesh} => MetaTriMesh::new(false, dm, builtin_tmv(mesh)?,
+    krp, 0, col),
+  PConvex{mesh} => MetaConvex::new(false, dm, builtin_fvp(mesh)?,
+    krp, 0, col),
+  PComposite{..} | PPolyhedron{..} => return None
+  })
+}
+
+/// shape and mass density of a Meta
+/// (None for trimesh and convex not built in, and for composite)
+fn prim_of(mi: &dyn MetaInf) -> Option<(Prim, dReal)> {
+  let v3 = |v: &dVector4| [v[0], v[1], v[2]];
+  Some(match mi.id() {
+  MetaId::Sphere => { let m = mi.as_sphere(); (PSphere{r: m.r}, m.dm) },
+  MetaId::Box => { let m = mi.as_box(); (PBox{lxyz: v3(&m.lxyz)}, m.dm) },
+  MetaId::Capsule => {
+    let m = mi.as_capsule(); (PCapsule{r: m.r, l: m.l}, m.dm) },
+  MetaId::Cylinder => {
+    let m = mi.as_cylinder(); (PCylinder{r: m.r, l: m.l}, m.dm) },
+  MetaId::Plane => {
+    let m = mi.as_plane();
+    (PPlane{lxyz: v3(&m.lxyz), norm: v3(&m.norm)}, m.dm) },
+  MetaId::TriMesh => {
+    let m = mi.as_trimesh();
+    let mesh = BUILTIN.into_iter().find(|n| builtin_tmv(n) == Some(m.tmv))?;
+    (PTriMesh{mesh: mesh.to_string()}, m.dm) },
+  MetaId::Convex => {
+    let m = mi.as_convex();
+    let mesh = BUILTIN.into_iter().find(|n| builtin_fvp(n) == Some(m.fvp))?;
+    (PConvex{mesh: mesh.to_string()}, m.dm) },
+  _ => return None
+  })
+}
+
+impl SceneFile {
+  /// parse toml and check presets roles catalogue names and parts
+  pub fn from_toml(s: &str) -> Result<Self, String> {
+    let f: SceneFile = toml::from_str(s).map_err(|e| e.to_string())?;
+    for (i, o) in f.object.iter().enumerate() {
+      o.check().map_err(|e| format!("object {}: {}", i, e))?;
+    }
+    Ok(f)
+  }
+
+  /// load from toml file
+  pub fn load(path: &str) -> Result<Self, String> {
+    let s = fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+    SceneFile::from_toml(&s).map_err(|e| format!("{}: {}", path, e))
+  }
+
+  /// FNV-1a hash of the objects written as toml (same for any path and layout)
+  pub fn hash(&self) -> u64 {
+    let s = toml::to_string(self).unwrap_or_default();
+    s.bytes().fold(0xcbf2_9ce4_8422_2325, |h, b|
+      (h ^ b as u64).wrapping_mul(0x0000_0100_0000_01b3))
+  }
+}
+
+impl SimApp {
+
+/// add scene file (built by start_callback after the scene)
+pub fn set_scene_file(&mut self, f: SceneFile) {
+  self.scene_file = Some(f);
+}
+
+/// create objects of the scene file (each error is printed and skipped)
+pub fn create_scene_file(&mut self, f: &SceneFile) {
+  for (i, o) in f.object.iter().enumerate() {
+    if let Err(e) = self.create_object(o) { println!("object {}: {}", i, e); }
+  }
+}
+
+/// create an object of the scene file (returns the key)
+pub fn create_object(&mut self, o: &ObjDef) -> Result<String, String> {
+  let role = o.role().ok_or(format!("role {}", o.role))?;
+  let krp = o.krp().ok_or(format!("krp {:?}", o.krp))?;
+  let p: dVector3 = [o.pos[0], o.pos[1], o.pos[2], 1.0];
+  let (k, body) = match &o.prim {
+  PPolyhedron{pe} => {
+    let pe = PE::from_name(pe).ok_or(format!("unknown PE: {}", pe))?;
+    let k = self.create_polyhedron(pe.0, p); // catalogue orientation
+    self.kgc(&k);
+    self.register_key(&k, Some(pe), role);
+    if o.rot.deg == 0.0 { return Ok(k); }
+    let body = self.super_get().find(k.clone())
+      .map_err(|e| e.to_string())?.body();
+    (k, body)
+  },
+  PComposite{parts} => {
+    let mut elems = vec![];
+    for t in parts.iter() {
+      let krp = t.krp.krp().ok_or(format!("krp {:?}", t.krp))?;
+      elems.push(meta(&t.prim, t.density, krp, t.col)
+        .ok_or("part must be box sphere capsule cylinder".to_string())?);
+    }
+    let mi = MetaComposite::new(elems,
+      parts.iter().map(|t| t.rot.q()).collect(),
+      parts.iter().map(|t|
+        [t.offset[0], t.offset[1], t.offset[2], 1.0]).collect(),
+      krp, 0, o.col());
+    let k = o.name.clone().unwrap_or_else(|| self.ts("composite"));
+    let body = self.create_meta(&k, mi, false, role);
+    (k, body)
+  },
+  prim => {
+    let mi = meta(prim, o.density(), krp, o.col())
+      .ok_or(format!("kind {:?}", prim))?;
+    let k = o.name.clone().unwrap_or_else(|| self.ts("object"));
+    let body = self.create_meta(&k, mi, o.total, role);
+    (k, body)
+  }
+  };
+  self.set_pos_Q(body, p, o.rot.q());
+  Ok(k)
+}
+
+/// create and register a Meta (total: dm is the total mass as creator_m)
+/// the definition is kept in the registry to be built again by restore
+pub fn create_meta(&mut self, k: &str, mi: Box<dyn MetaInf>, total: bool,
+  role: Role) -> dBodyID {
+  let def = ObjDef::from_meta(k, &*mi, total, role);
+  let rode = self.super_mut();
+  let (body, _, _) = match mi.id() {
+  MetaId::Composite => rode.creator_composite(k, mi),
+  _ if total => rode.creator_m(k, mi),
+  _ => rode.creator(k, mi)
+  };
+  self.register(body, None, role);
+  if let Some(def) = def { self.reg.set_def(body, def); }
+  body
+}
+
+}
@@ -0,0 +1,452 @@
+/*
+  scenes.rs for c60
+
+  named scenes (game, sandbox, c60-gallery, slope-lab, empty)
+  and test scene builders
+*/
+
+use ode_rs::colors::*;
+use ode_rs::ode::*;
+
+use crate::game::SimApp;
+use crate::registry::*;
+use crate::polyhedron::setup_polyhedron_sequence;
+
+/// scene built by start_callback
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Scene {
+  /// game arena
+  SGame,
+  /// open field with all test objects and the c60 gallery (with game)
+  SSandbox,
+  /// c60 polyhedra only
+  SC60Gallery,
+  /// slope, tmball and trimesh objects around the bunny
+  SSlopeLab,
+  /// ground only
+  SEmpty
+}
+pub use Scene::*;
+
+impl Scene {
+  /// all scenes
+  pub const ALL: [Scene; 5] = [SGame, SSandbox, SC60Gallery, SSlopeLab, SEmpty];
+
+  /// name given on the command line
+  pub fn name(&self) -> &'static str {
+    match self {
+    SGame => "game",
+    SSandbox => "sandbox",
+    SC60Gallery => "c60-gallery",
+    SSlopeLab => "slope-lab",
+    SEmpty => "empty"
+    }
+  }
+
+  /// scene from name (None when not found)
+  pub fn from_name(s: &str) -> Option<Scene> {
+    Scene::ALL.into_iter().find(|sc| sc.name() == s)
+  }
+
+  /// true when the merge game is active in this scene
+  pub fn is_game(&self) -> bool {
+    matches!(self, SGame | SSandbox)
+  }
+}
+
+impl SimApp {
+
+/// select the scene built by start_callback
+pub fn set_scene(&mut self, s: Scene) {
+  self.scene = s;
+}
+
+/// true when the merge game is active in the current scene
+pub fn is_game(&self) -> bool {
+  self.scene.is_game()
+}
+
+/// create the scene (polyhedron sequence is set up here for all scenes)
+pub fn create_scene(&mut self) {
+  println!("scene: {}", self.scene.name());
+  setup_polyhedron_sequence();
+  match self.scene {
+  SGame => { self.create_arena(); },
+  SSandbox => {
+    self.create_sandbox();
+    self.create_c60_gallery();
+  },
+  SC60Gallery => { self.create_c60_gallery(); },
+  SSlopeLab => { self.create_slope_lab(); },
+  SEmpty => {}
+  }
+  if let Some(f) = self.scene_file.take() { // take to skip borrow
+    self.create_scene_file(&f);
+    self.scene_file = Some(f);
+  }
+}
+
+/// create game arena (walls from MetaBox panels, drop zone above)
+pub fn create_arena(&mut self) {
+  let [w, d, h] = self.arena.size;
+  let [cx, cy] = self.arena.center;
+  let t = self.arena.thick;
+  let m = t / 2.0 + self.arena.radius; // hold pos margin from the walls
+  let panels = [
+    ([t, d + 2.0 * t, h, 0.0], [cx - (w + t) / 2.0, cy, h / 2.0, 1.0]),
+    ([t, d + 2.0 * t, h, 0.0], [cx + (w + t) / 2.0, cy, h / 2.0, 1.0]),
+    ([w, t, h, 0.0], [cx, cy - (d + t) / 2.0, h / 2.0, 1.0]),
+    ([w, t, h, 0.0], [cx, cy + (d + t) / 2.0, h / 2.0, 1.0])];
+  for (i, (lxyz, p)) in panels.into_iter().enumerate() {
+    let krp = Krp::new(false, false, true, 0.95, 0.3); // pos kept (q by hold_walls)
+    let mi_wall = MetaBox::new(1.0, lxyz, krp, 0, [0.6, 0.6, 0.8, 0.4]);
+    let body = self.create_meta(&format!("arena_wall_{}", i), mi_wall,
+      false, RFixture);
+    self.set_pos_Q(body, p, QI);
+  }
+  let z = h + self.arena.drop;
+  self.pos = [cx, cy, z, 1.0];
+  self.nexpos = [cx, cy, z + 4.0, 1.0];
+  self.holdpos = [cx, cy - d / 2.0 - 3.0, z + 4.0, 1.0];
+  let (bx, by) = (w / 2.0 - m, d / 2.0 - m);
+  self.bounds = Some([cx - bx.max(0.0), cx + bx.max(0.0),
+    cy - by.max(0.0), cy + by.max(0.0)]);
+  if !self.limit_set { self.limit.0 = h; }
+}
+
+/// keep the arena walls upright (Krp k false restores only the position)
+pub fn hold_walls(&mut self) {
+  let rode = self.super_mut();
+  for i in 0..4 {
+    let Ok(o) = rode.find_mut(format!("arena_wall_{}", i)) else { continue; };
+    o.set_quaternion(QI).set_angular_vel([0.0, 0.0, 0.0, 0.0]);
+  }
+}
+
+/// create sandbox (all test objects)
+pub fn create_sandbox(&mut self) {
+  self.create_test_balls();
+  self.create_test_ball_big();
+  self.create_test_box_small();
+  self.create_test_box_frames();
+  self.create_test_capsule_frames();
+  self.create_test_cylinder_frames();
+  self.create_test_composite();
+  self.create_test_tetra();
+  self.create_test_cube();
+  self.create_test_icosahedron();
+//  self.create_test_plane();
+
+  self.create_slope_lab();
+}
+
+/// create slope lab (slope, tmball and trimesh objects around the bunny)
+pub fn create_slope_lab(&mut self) {
+  self.create_tmball();
+  self.create_slope();
+  self.create_sphere_apple();
+  self.create_sphere_ball(); self.create_sphere_roll();
+  self.create_tmtetra();
+  self.create_tmcube();
+  self.create_tmicosahedron();
+  self.create_tmbunny();
+}
+
+/// create c60 gallery (polyhedron sequence must be set up before)
+pub fn create_c60_gallery(&mut self) {
+  self.create_c60_icosahedron();
+  self.create_c60_dodecahedron();
+  self.create_c60_dodecahedron_center();
+  self.create_c60_fullerene();
+  self.create_c60_fullerene_center();
+}
+
+/// create test balls
+pub fn create_test_balls(&mut self) {
+  let m: dReal = 0.8;
+  let r: dReal = 0.2;
+  for i in 0..16 {
+    let c: dVector4 = vec4_from_u32(COLORS[i]);
+    let p: dVector3 = [(i%4) as dReal - 1.5, (i/4) as dReal - 1.5, 2.0, 1.0];
+    let mib = MetaSphere::new(m, r, KRP095, 0, c);
+    let body = self.create_meta(&format!("ball_{:08X}", i), mib, true, RTest);
+    self.set_pos_Q(body, p, QI);
+  }
+}
+
+/// create test ball big
+pub fn create_test_ball_big(&mut self) {
+  let c: dVector4 = [1.0, 1.0, 0.0, 0.8];
+  let p: dVector3 = [0.0, 0.0, 10.0, 1.0];
+  let mib = MetaSphere::new(0.08 / (125.0 * PIt4), 1.0, KRP095, 0, c);
+  let body = self.create_meta("ball_big", mib, false, RTest);
+  self.set_pos_Q(body, p, QI);
+}
+
+/// create test box small
+pub fn create_test_box_small(&mut self) {
+  let mibox_small = MetaBox::new(0.1, [1.0, 1.0, 1.0, 0.0],
+    KRP095, 0, [0.0, 1.0, 1.0, 0.8]);
+  let body = self.create_meta("box_small", mibox_small, false, RTest);
+  self.set_pos_Q(body, [-5.0, 5.0, 2.0, 1.0], QI);
+}
+
+/// create test box frames
+pub fn create_test_box_frames(&mut self) {
+  let mibox_big_0 = MetaBox::new(0.1, [1.0, 5.0, 0.5, 0.0],
+    KRP095, 0, [1.0, 0.0, 1.0, 0.8]);
+  let body = self.create_meta("box_big_0", mibox_big_0, false, RFixture);
+  self.set_pos_R(body, [-9.0, -11.0, 2.0, 1.0],
+    dMatrix3::from_axis_and_angle([0.0, 0.0, 1.0], PIx));
+
+  let mibox_big_1 = MetaBox::new(0.1, [1.0, 12.0, 0.5, 0.0],
+    KRP095, 0, [0.0, 1.0, 0.0, 0.8]);
+  let body = self.create_meta("box_big_1", mibox_big_1, false, RFixture);
+  self.set_pos_R(body, [12.0, -12.0, 2.0, 1.0],
+    dMatrix3::from_axis_and_angle([0.0, 0.0, 1.0], -PIq));
+
+  let mibox_big_2 = MetaBox::new(0.1, [1.0, 12.0, 0.5, 0.0],
+    KRP095, 0, [0.0, 1.0, 1.0, 0.8]);
+  let body = self.create_meta("box_big_2", mibox_big_2, false, RFixture);
+  self.set_pos_R(body, [12.0, 12.0, 2.0, 1.0],
+    dMatrix3::from_axis_and_angle([0.0, 0.0, 1.0], PIq));
+
+  let mibox_big_3 = MetaBox::new(0.1, [1.0, 12.0, 0.5, 0.0],
+    KRP095, 0, [0.0, 0.0, 1.0, 0.8]);
+  let body = self.create_meta("box_big_3", mibox_big_3, false, RFixture);
+  self.set_pos_R(body, [-12.0, 12.0, 2.0, 1.0],
+    dMatrix3::from_axis_and_angle([0.0, 0.0, 1.0], -PIq));
+}
+
+/// create test capsule frames
+pub fn create_test_capsule_frames(&mut self) {
+  let micap_0 = MetaCapsule::new(0.001, 0.5, 16.0,
+    KRP080, 0, [0.0, 1.0, 0.0, 0.8]);
+  let body = self.create_meta("p_capsule_0", micap_0, false, RFixture);
+  self.set_pos_R(body, [-8.6, 0.0, 1.5, 1.0],
+    dMatrix3::from_axis_and_angle([1.0, 0.0, 0.0], PIh));
+
+  let micap_1 = MetaCapsule::new(0.001, 0.5, 16.0,
+    KRP080, 0, [0.0, 0.0, 1.0, 0.8]);
+  let body = self.create_meta("p_capsule_1", micap_1, false, RFixture);
+  self.set_pos_R(body, [8.6, 0.0, 1.5, 1.0],
+    dMatrix3::from_axis_and_angle([1.0, 0.0, 0.0], PIh));
+}
+
+/// create test cylinder frames
+pub fn create_test_cylinder_frames(&mut self) {
+  let micyl_0 = MetaCylinder::new(0.001, 0.5, 16.0,
+    KRP080, 0, [1.0, 0.0, 1.0, 0.8]);
+  let body = self.create_meta("p_cylinder_0", micyl_0, false, RFixture);
+  self.set_pos_R(body, [0.0, 8.6, 1.5, 1.0],
+    dMatrix3::from_axis_and_angle([0.0, 1.0, 0.0], PIh));
+
+  let micyl_1 = MetaCylinder::new(0.001, 0.5, 16.0,
+    KRP080, 0, [0.0, 1.0, 1.0, 0.8]);
+  let body = self.create_meta("p_cylinder_1", micyl_1, false, RFixture);
+  self.set_pos_R(body, [0.0, -8.6, 1.5, 1.0],
+    dMatrix3::from_axis_and_angle([0.0, 1.0, 0.0], PIh));
+}
+
+/// create test composite
+pub fn create_test_composite(&mut self) {
+  let micmp_0 = MetaComposite::new(
+    vec![
+      MetaBox::new(0.1, [0.5, 0.5, 0.5, 0.0], KRP095, 0, [1.0, 0.0, 0.0, 0.8]),
+      MetaBox::new(0.1, [0.5, 0.5, 0.5, 0.0], KRP095, 0, [0.0, 0.0, 1.0, 0.8]),
+      MetaSphere::new(0.6 / PI, 0.5, KRP095, 0, [0.0, 1.0, 0.0, 0.8]),
+      MetaSphere::new(0.0001, 0.1, KRPnk, 0, [1.0, 0.0, 1.0, 0.8])],
+    vec![QI, QI, QI, QI],
+    vec![
+      [-0.4, -0.4, -0.4, 1.0],
+      [0.4, 0.4, 0.4, 1.0],
+      [0.0, 0.0, 0.0, 1.0],
+      [0.0, 0.0, 0.0, 1.0]],
+    KRPnk, 0, [1.0, 0.0, 0.0, 0.8]);
+  let body = self.create_meta("composite_0", micmp_0, false, RTest);
+  self.set_pos_Q(body, [-12.0, -2.0, 2.0, 1.0],
+    dQuaternion::from_axis_and_angle([0.0, 0.0, 1.0], -PIq3));
+
+  let micmp_1 = MetaComposite::new(
+    vec![
+      MetaBox::new(0.1, [0.5, 0.5, 0.5, 0.0], KRP095, 0, [1.0, 0.0, 0.0, 0.8]),
+      MetaBox::new(0.1, [0.5, 0.5, 0.5, 0.0], KRP095, 0, [0.0, 0.0, 1.0, 0.8]),
+      MetaSphere::new(0.6 / PI, 0.5, KRP095, 0, [0.0, 1.0, 0.0, 0.8])],
+    vec![
+      dQuaternion::from_axis_and_angle([-0.707, 0.707, 0.0], PIq),
+      dQuaternion::from_axis_and_angle([0.707, -0.707, 0.0], -PIq),
+      dQuaternion::new()],
+    vec![
+      [-0.4, -0.4, -0.4, 1.0],
+      [0.4, 0.4, 0.4, 1.0],
+      [0.0, 0.0, 0.0, 1.0]],
+    KRP100, 0, [1.0, 0.0, 0.0, 0.8]);
+  let body = self.create_meta("composite_1", micmp_1, false, RTest);
+  self.set_pos_Q(body, [-12.0, 0.0, 2.0, 1.0],
+    dQuaternion::from_axis_and_angle([0.0, 0.0, 1.0], -PIq3));
+}
+
+/// create test tetra
+pub fn create_test_tetra(&mut self) {
+  let mitmv_tetra_0 = MetaTriMesh::new(false, 0.1, unsafe { &mut *tetra::tmv },
+    KRP095, 0, [1.0, 0.0, 0.0, 0.8]);
+  let body = self.create_meta("tmv_tetra_0", mitmv_tetra_0, false, RTest);
+  self.set_pos_Q(body, [-13.0, -6.0, 2.0, 1.0], QI);
+
+  let mifvp_tetra_0 = MetaConvex::new(false, 0.1, unsafe { &mut *tetra::fvp },
+    KRP095, 0, [0.0, 0.0, 1.0, 0.8]);
+  let body = self.create_meta("fvp_tetra_0", mifvp_tetra_0, false, RTest);
+  self.set_pos_Q(body, [-13.0, -8.0, 2.0, 1.0], QI);
+}
+
+/// create test cube
+pub fn create_test_cube(&mut self) {
+  let mitmv_cube_0 = MetaTriMesh::new(false, 0.1, unsafe { &mut *cube::tmv },
+    KRP095, 0, [1.0, 1.0, 0.0, 0.8]);
+  let body = self.create_meta("tmv_cube_0", mitmv_cube_0, false, RTest);
+  self.set_pos_Q(body, [-7.0, 1.0, 2.0, 1.0], QI);
+
+  let mifvp_cube_0 = MetaConvex::new(false, 0.1, unsafe { &mut *cube::fvp },
+    KRP095, 0, [1.0, 0.0, 1.0, 0.8]);
+  let body = self.create_meta("fvp_cube_0", mifvp_cube_0, false, RTest);
+  self.set_pos_Q(body, [-7.0, -1.0, 2.0, 1.0], QI);
+}
+
+/// create test icosahedron
+pub fn create_test_icosahedron(&mut self) {
+  let mitmv_ih_0 = MetaTriMesh::new(false, 0.1,
+    unsafe { &mut *icosahedron::tmv },
+    KRP095, 0, [0.0, 1.0, 1.0, 0.8]);
+  let body = self.create_meta("tmv_icosahedron_0", mitmv_ih_0, false, RTest);
+  self.set_pos_Q(body, [-7.0, 3.0, 2.0, 1.0], QI);
+
+  let mifvp_ih_0 = MetaConvex::new(false, 0.1,
+    unsafe { &mut *icosahedron::fvp },
+    KRP095, 0, [1.0, 1.0, 0.0, 0.8]);
+  let body = self.create_meta("fvp_icosahedron_0", mifvp_ih_0, false, RTest);
+  self.set_pos_Q(body, [-7.0, -3.0, 2.0, 1.0], QI);
+}
+
+/// create test plane
+pub fn create_test_plane(&mut self) {
+  let dm: dReal = 0.1;
+  let lxyz: dVector3 = [10.0, 10.0, 0.05, 0.0];
+  let norm: dVector4 = [0.0, 0.0, 1.0, 0.0];
+  let col: dVector4 = vec4_from_u32(COLORS[0]);
+  let pos: dVector3 = [-5.0, -5.0, 5.0, 0.0];
+  let mip = MetaPlane::new(dm, lxyz, norm, KRPnk, 0, col);
+  let body = self.create_meta("plane", mip, false, RFixture);
+  let q = dQuaternion::from_axis_and_angle([1.0, 1.0, 0.0], PIq);
+  self.super_mut().get_mut(body).expect("fail reg").set_pos(pos)
+    // .set_rot(dMatrix3::from_z_axis([0.7, 0.7, 0.0]));
+    // .set_rot(dMatrix3::from_2_axes([-0.7, 0.7, 0.0], [0.7, 0.7, 0.0]));
+    // .set_rot(dMatrix3::from_euler_angles(PIq, PIq, PIq));
+    // .set_rot(dMatrix3::from_axis_and_angle([0.0, 0.0, 1.0], PIq));
+    // .set_rot(dMatrix3::new());
+    // .set_rot(dMatrix3::from_Q(dQuaternion::new()));
+    // .set_rot(dQuaternion::new().to_R());
+    // .set_quaternion(dMatrix3::new().to_Q());
+    // .set_quaternion(dQuaternion::from_R(dMatrix3::new()));
+    // .set_quaternion(dQuaternion::new());
+    // .set_quaternion(q);
+    .set_rot(q.to_R());
+}
+
+/// create
+pub fn create_tmball(&mut self) {
+  let mi_tmball = MetaComposite::new(
+    vec![
+      MetaTriMesh::new(false, 0.1, unsafe { &mut *bunny::tmv },
+        KRP095, 0, [1.0, 0.8, 0.2, 0.6]),
+      MetaSphere::new(0.1, 1.2, KRP095, 0, [0.2, 1.0, 0.8, 0.4])],
+    vec![QI, QI],
+    vec![[0.0, 0.0, 0.0, 0.0], [0.0, 0.0, 0.0, 0.0]],
+    KRP100, 0, [1.0, 0.0, 0.0, 0.8]);
+  let k = self.ts("tmball");
+  let body = self.create_meta(&k, mi_tmball, false, RTest);
+  let p = dQuaternion::from_axis_and_angle([0.0, 0.0, 1.0], PIh);
+  let q = dQuaternion::from_axis_and_angle([1.0, 0.0, 0.0], PIh);
+  let o = dQuaternion::multiply0(p, q);
+  self.set_pos_Q(body, [-29.0, -3.0, 3.0, 1.0], o);
+}
+
+/// create
+pub fn create_slope(&mut self) {
+  let mi_slope = MetaComposite::new(
+    vec![
+      MetaBox::new(1.0, [6.0, 0.1, 8.0, 0.0], KRP001, 0, [1.0, 0.8, 0.2, 0.6]),
+      MetaCylinder::new(1.0, 1.0, 2.0, KRP001, 0, [0.2, 1.0, 0.8, 0.4])],
+    vec![QI, QI],
+    vec![[0.0, 0.0, 0.0, 0.0], [-3.0, 0.0, 0.0, 0.0]],
+    KRP100, 0, [1.0, 0.0, 0.0, 0.8]);
+  let body = self.create_meta("slope", mi_slope, false, RFixture);
+  let p = dQuaternion::from_axis_and_angle([0.0, 1.0, 0.0], PIx / 3.0);
+  let q = dQuaternion::from_axis_and_angle([1.0, 0.0, 0.0], PIh);
+  let o = dQuaternion::multiply0(p, q);
+  self.set_pos_Q(body, [-28.5, 0.0, 1.2, 1.0], o);
+}
+
+/// create x, y on the bunny
+pub fn create_sphere_apple(&mut self) {
+  let krp = Krp::new(true, false, true, 0.95, 0.1);
+  let mi_apple = MetaSphere::new(0.1, 0.2, krp, 0, [0.8, 0.4, 0.4, 0.8]);
+  let body = self.create_meta("apple", mi_apple, false, RTest);
+  self.set_pos_Q(body, [-15.15, 0.31, 2.5, 1.0], QI);
+}
+
+/// create
+pub fn create_sphere_ball(&mut self) {
+  let mi_ball = MetaSphere::new(0.1, 0.1, KRP080, 0, [0.4, 0.4, 0.8, 0.8]);
+  let z = mi_ball.r;
+  let body = self.create_meta("ball", mi_ball, false, RTest);
+  self.set_pos_Q(body, [-14.5, 0.0, z, 1.0], QI);
+}
+
+/// create on the slope
+pub fn create_sphere_roll(&mut self) {
+  let mi_roll = MetaSphere::new(0.1, 0.2, KRP080, 0, [0.4, 0.8, 0.4, 0.8]);
+  let body = self.create_meta("roll", mi_roll, false, RTest);
+  self.set_pos_Q(body, [-27.0, 0.0, 1.2, 1.0], QI);
+}
+
+/// create
+pub fn create_tmtetra(&mut self) {
+  let mi_tmtetra = MetaTriMesh::new(false, 1.0, unsafe { &mut *tetra::tmv },
+    KRP095, 0, [0.8, 0.6, 0.2, 1.0]);
+  let k = self.ts("tmtetra");
+  let body = self.create_meta(&k, mi_tmtetra, false, RTest);
+  self.set_pos_Q(body, [-15.0, -1.5, 0.5, 1.0], QI);
+}
+
+/// create
+pub fn create_tmcube(&mut self) {
+  let mi_tmcube = MetaTriMesh::new(false, 1.0, unsafe { &mut *cube::tmv },
+    KRP095, 0, [0.6, 0.8, 0.2, 1.0]);
+  let k = self.ts("tmcube");
+  let body = self.create_meta(&k, mi_tmcube, false, RTest);
+  self.set_pos_Q(body, [-16.5, -3.0, 0.5, 1.0],
+    dQuaternion::from_axis_and_angle([1.0, 1.0, 1.0], PIq));
+}
+
+/// create
+pub fn create_tmicosahedron(&mut self) {
+  let mi_tmih = MetaTriMesh::new(false, 1.0, unsafe { &mut *icosahedron::tmv },
+    KRP095, 0, [0.2, 0.8, 0.6, 1.0]);
+  let k = self.ts("tmicosahedron");
+  let body = self.create_meta(&k, mi_tmih, false, RTest);
+  self.set_pos_Q(body, [-16.5, 3.0, 0.5, 1.0], QI);
+}
+
+/// create
+pub fn create_tmbunny(&mut self) {
+  let mi_tmbunny = MetaTriMesh::new(false, 1.0, unsafe { &mut *bunny::tmv },
+    KRP095, 0, [0.8, 0.2, 0.6, 1.0]);
+  let k = self.ts("tmbunny");
+  let body = self.create_meta(&k, mi_tmbunny, false, RTest);
+  // phi=-x, theta=-y, psi=-z
+  let m = dMatrix3::from_euler_angles(-PIh, 0.0, 0.0);
+  self.set_pos_Q(body, [-15.0, 0.25, 0.88, 1.0], dQuaternion::from_R(m));
+  // to (-0.109884, 0.304591, 1.217693)
+}
+
+}
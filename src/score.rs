@@ -0,0 +1,194 @@
+/*
+  score.rs for c60
+
+  score model for polyhedron merges
+*/
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use crate::polyhedron::PE;
+
+/// merge event
+#[derive(Debug, Clone)]
+pub struct MergeEvent {
+  /// simulation step
+  pub step: usize,
+  /// mesh key of the merged kind
+  pub key: String,
+  /// number of merged bodies
+  pub n: usize,
+  /// chain counter (0: not a combo)
+  pub chain: usize,
+  /// score multiplier (chain + 1)
+  pub mult: usize,
+  /// points added
+  pub pts: usize,
+  /// key of the spawned successor
+  pub spawned: String
+}
+
+impl fmt::Display for MergeEvent {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "merge {} x{} at {} chain {} (x{}) +{} -> {}",
+      self.key, self.n, self.step, self.chain, self.mult, self.pts,
+      self.spawned)
+  }
+}
+
+/// running score and merge counts per tier of the evolution chain
+#[derive(Debug, Clone)]
+pub struct Score {
+  /// running total
+  pub total: usize,
+  /// merge counts per tier
+  pub merges: Vec<usize>,
+  /// steps a merged body keeps the chain alive (combo window)
+  pub window: usize,
+  /// longest chain
+  pub max_chain: usize,
+  /// tier &lt;key, tier&gt; (position in the evolution chain)
+  tiers: HashMap<String, usize>
+}
+
+impl Score {
+  /// tiers walk the evolution chain from root (see Score::root)
+  pub fn new(evo: &HashMap<String, PE>, ped: &[PE]) -> Self {
+    let tiers: HashMap<String, usize> = Score::chain(evo, &Score::root(evo, ped))
+      .into_iter().enumerate().map(|(t, k)| (k, t)).collect();
+    Score{total: 0, merges: vec![0; tiers.len()], window: 500, max_chain: 0,
+      tiers}
+  }
+
+  /// keys from k along the evolution chain (stop when the chain loops)
+  pub fn chain(evo: &HashMap<String, PE>, k: &str) -> Vec<String> {
+    let mut ks: Vec<String> = vec![];
+    let mut k = k.to_string();
+    while !ks.contains(&k) {
+      ks.push(k.clone());
+      let Some(pe) = evo.get(&k) else { break; };
+      k = pe.key().to_string();
+    }
+    ks
+  }
+
+  /// root of the evolution chain: the key nothing evolves into
+  /// (the longest chain when there are several, the first drop in the loop
+  /// when the chain is closed)
+  pub fn root(evo: &HashMap<String, PE>, ped: &[PE]) -> String {
+    let next: HashSet<&str> = evo.values().map(|pe| pe.key()).collect();
+    let mut roots: Vec<&String> =
+      evo.keys().filter(|k| !next.contains(k.as_str())).collect();
+    roots.sort();
+    let mut root: Option<(&String, usize)> = None;
+    for k in roots {
+      let n = Score::chain(evo, k).len();
+      if !matches!(root, Some((_, m)) if m >= n) { root = Some((k, n)); }
+    }
+    if let Some((k, _)) = root { return k.to_string(); }
+    match ped.iter().find(|pe| evo.contains_key(pe.key())) {
+    Some(pe) => pe.key().to_string(),
+    None => ped.first().map_or("".to_string(), |pe| pe.key().to_string())
+    }
+  }
+
+  /// tier of the mesh key (None when not in the evolution chain)
+  pub fn tier(&self, k: &str) -> Option<usize> {
+    self.tiers.get(k).copied()
+  }
+
+  /// points for a merge of tier (triangular number 1 3 6 10 ...)
+  pub fn points(tier: usize) -> usize {
+    (tier + 1) * (tier + 2) / 2
+  }
+
+  /// count a merge of key k and returns points added
+  pub fn merge(&mut self, k: &str) -> usize {
+    let Some(tier) = self.tier(k) else { return 0; };
+    let pts = Score::points(tier);
+    self.merges[tier] += 1;
+    self.total += pts;
+    pts
+  }
+
+  /// count a merge of n bodies of key k (bonus for n over 2)
+  /// multiplied by chain + 1, and returns points added
+  pub fn merge_n(&mut self, k: &str, n: usize, chain: usize) -> usize {
+    let pts = self.merge(k);
+    let bonus = if n > 2 { pts * (n - 2) } else { 0 };
+    let extra = (pts + bonus) * (chain + 1) - pts;
+    self.total += extra;
+    if chain > self.max_chain { self.max_chain = chain; }
+    pts + extra
+  }
+
+  /// clear total and counts (keep tiers)
+  pub fn reset(&mut self) {
+    self.total = 0;
+    self.max_chain = 0;
+    for m in self.merges.iter_mut() { *m = 0; }
+  }
+}
+
+impl fmt::Display for Score {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let mut ks: Vec<(&String, &usize)> = self.tiers.iter().collect();
+    ks.sort_by_key(|&(_, t)| *t);
+    writeln!(f, "score: {} (max chain {})", self.total, self.max_chain)?;
+    for (k, &t) in ks {
+      writeln!(f, " tier {:2} {:20} x {:4} ({} pts)",
+        t, k, self.merges[t], Score::points(t))?;
+    }
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn pe(n: &str) -> PE {
+    PE::from_name(n).expect(n)
+  }
+
+  /// tetra -> cube_center -> octa (cone and octa drop, no loop)
+  fn evo() -> HashMap<String, PE> {
+    [("tetra", "ECubeCenter"), ("cube_center", "EOcta"), ("cone", "ETetra")]
+      .into_iter().map(|(k, n)| (k.to_string(), pe(n))).collect()
+  }
+
+  #[test]
+  fn triangular_points() {
+    let pts: Vec<usize> = (0..5).map(Score::points).collect();
+    assert_eq!(pts, vec![1, 3, 6, 10, 15]);
+  }
+
+  #[test]
+  fn root_and_tiers() {
+    let s = Score::new(&evo(), &[pe("EOcta")]);
+    assert_eq!(Score::root(&evo(), &[]), "cone");
+    let tiers: Vec<Option<usize>> = ["cone", "tetra", "cube_center", "octa",
+      "ring"].iter().map(|k| s.tier(k)).collect();
+    assert_eq!(tiers, vec![Some(0), Some(1), Some(2), Some(3), None]);
+  }
+
+  #[test]
+  fn root_of_loop_is_first_drop_in_it() {
+    let mut e = evo();
+    e.insert("octa".to_string(), pe("ECone"));
+    assert_eq!(Score::root(&e, &[pe("ERing"), pe("ECubeCenter")]),
+      "cube_center");
+  }
+
+  #[test]
+  fn chain_scoring() {
+    let mut s = Score::new(&evo(), &[]);
+    assert_eq!(s.merge_n("tetra", 2, 0), 3); // tier 1
+    assert_eq!(s.merge_n("tetra", 3, 0), 6); // bonus for the third body
+    assert_eq!(s.merge_n("cube_center", 2, 2), 18); // tier 2 x3
+    assert_eq!(s.merge_n("ring", 2, 1), 0); // not in the chain
+    assert_eq!((s.total, s.max_chain, s.merges.clone()), (27, 2, vec![0, 2, 1, 0]));
+    s.reset();
+    assert_eq!((s.total, s.max_chain, s.merges), (0, 0, vec![0; 4]));
+  }
+}
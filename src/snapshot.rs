@@ -0,0 +1,324 @@
+/*
+  snapshot.rs for c60
+
+  world snapshot (registered objects and game state) save and load
+  and undo of the last drops
+*/
+
+use serde::{Serialize, Deserialize};
+
+use ode_rs::ode::*;
+use ode_base::ode::{dBodyGetFirstGeom, dBodyGetNextGeom, dGeomGetClass,
+  dGeomTransformGetGeom, dGeomTransformClass};
+
+use rand_chacha::ChaCha8Rng;
+use rand::SeedableRng;
+
+use std::fs;
+
+use crate::game::{SimApp, Phase, PEmpty};
+use crate::polyhedron::PE;
+use crate::registry::*;
+use crate::scenefile::ObjDef;
+
+/// state of a registered object
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ObjState {
+  /// key
+  pub key: String,
+  /// catalogue name (None: not a catalogue polyhedron)
+  pub pe: Option<String>,
+  /// definition to build again (None: catalogue polyhedron)
+  pub def: Option<ObjDef>,
+  /// role name
+  pub role: String,
+  /// simulation step created on
+  pub step: usize,
+  /// chain counter of the merge that spawned this
+  pub chain: Option<usize>,
+  /// position
+  pub pos: [dReal; 3],
+  /// quaternion
+  pub q: [dReal; 4],
+  /// linear velocity
+  pub lvel: [dReal; 3],
+  /// angular velocity
+  pub avel: [dReal; 3],
+  /// enabled
+  pub enabled: bool,
+  /// colour
+  pub col: [dReal; 4],
+  /// Krp flags k g c of each geom (the parts of a composite)
+  pub kgc: Vec<[bool; 3]>
+}
+
+/// world snapshot (every registered object is built again)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Snapshot {
+  /// scene name
+  pub scene: String,
+  /// simulation steps
+  pub steps: usize,
+  /// drop rng as "seed word_pos" (toml has no u64 over i64 nor u128)
+  #[serde(default)]
+  pub rng: String,
+  /// phase (as usize)
+  pub phase: usize,
+  /// current hold key
+  pub current: String,
+  /// hold pos
+  pub pos: [dReal; 3],
+  /// next drop keys (preview queue)
+  pub nexkeys: Vec<String>,
+  /// hold slot key
+  pub holdkey: Option<String>,
+  /// true after hold or swap until the next drop
+  pub swapped: bool,
+  /// released pieces
+  pub pieces: Vec<String>,
+  /// seconds a settled piece stays above the limit line
+  pub over: dReal,
+  /// score total
+  pub total: usize,
+  /// score merge counts per tier
+  pub merges: Vec<usize>,
+  /// score longest chain
+  pub max_chain: usize,
+  /// registered objects
+  pub object: Vec<ObjState>
+}
+
+impl Snapshot {
+  /// parse toml
+  pub fn from_toml(s: &str) -> Result<Self, String> {
+    toml::from_str(s).map_err(|e| e.to_string())
+  }
+
+  /// write to path as toml
+  pub fn save(&self, path: &str) -> Result<(), String> {
+    let s = toml::to_string(self).map_err(|e| e.to_string())?;
+    fs::write(path, format!("# c60 snapshot\n{}", s))
+      .map_err(|e| format!("{}: {}", path, e))
+  }
+
+  /// load from toml file
+  pub fn load(path: &str) -> Result<Self, String> {
+    let s = fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+    Snapshot::from_toml(&s).map_err(|e| format!("{}: {}", path, e))
+  }
+}
+
+/// geoms holding the Krp of the body (the parts of a composite in body order)
+fn krp_geoms(id: dBodyID, g: dGeomID) -> Vec<dGeomID> {
+  if g != 0 as dGeomID { return vec![g]; }
+  let mut gs = vec![];
+  unsafe {
+    let mut t = dBodyGetFirstGeom(id);
+    while t != 0 as dGeomID {
+      let sub = dGeomGetClass(t) == dGeomTransformClass as _; // enum type differs by target
+      gs.push(if sub { dGeomTransformGetGeom(t) } else { t });
+      t = dBodyGetNextGeom(t);
+    }
+  }
+  gs
+}
+
+/// seed and word pos of the drop rng ("seed word_pos")
+fn parse_rng(s: &str) -> Option<(u64, u128)> {
+  let (seed, pos) = s.split_once(' ')?;
+  Some((seed.parse().ok()?, pos.parse().ok()?))
+}
+
+/// new key of the snapshot key k (k when it is not in keys)
+fn new_key(keys: &[(String, String)], k: &String) -> String {
+  keys.iter().find(|(o, _)| o == k).map_or(k.clone(), |(_, n)| n.clone())
+}
+
+impl SimApp {
+
+/// set snapshot path (written by 'e' and read by 'E')
+pub fn set_snapshot_path(&mut self, path: &str) {
+  self.snap_path = path.to_string();
+}
+
+/// restore the snapshot after the scene is built
+pub fn load_snapshot_at_start(&mut self, s: Snapshot) {
+  self.snap_start = Some(s);
+}
+
+/// take snapshot of every registered object and the game state
+pub fn snapshot(&mut self) -> Snapshot {
+  let ids: Vec<(dBodyID, Entry)> = self.reg.iter()
+    .map(|(&id, e)| (id, e.clone())).collect();
+  let mut object = vec![];
+  for (id, e) in ids.into_iter() {
+    let rode = self.super_mut();
+    let Ok(obg) = rode.get(id) else { continue; };
+    let (key, mut col, enabled, g) = (obg.key.clone(), obg.col,
+      obg.is_enabled(), obg.geom());
+    if let Ok(mgm) = rode.get_mgm(g) { col = mgm.get_tcm().col; }
+    let kgc = krp_geoms(id, g).into_iter()
+      .map(|g| rode.get_krp(g)).map(|krp| [krp.k, krp.g, krp.c]).collect();
+    let p = Obg::get_pos_mut_by_id(id);
+    let q = Obg::get_quaternion_mut_by_id(id);
+    let lv = Obg::get_linear_vel_mut_by_id(id);
+    let av = Obg::get_angular_vel_mut_by_id(id);
+    object.push(ObjState{key, pe: e.pe.map(|pe| pe.def().name.to_string()),
+      def: e.def, role: e.role.name().to_string(), step: e.step, chain: e.chain,
+      pos: [p[0], p[1], p[2]], q: [q[0], q[1], q[2], q[3]],
+      lvel: [lv[0], lv[1], lv[2]], avel: [av[0], av[1], av[2]],
+      enabled, col, kgc});
+  }
+  object.sort_by(|a, b| (a.step, &a.key).cmp(&(b.step, &b.key))); // creation order
+  Snapshot{scene: self.scene.name().to_string(), steps: self.steps,
+    rng: format!("{} {}", self.seed, self.rng.get_word_pos()),
+    phase: self.phase as usize,
+    current: self.current.clone(), pos: [self.pos[0], self.pos[1], self.pos[2]],
+    nexkeys: self.nexkeys.iter().cloned().collect(),
+    holdkey: self.holdkey.clone(), swapped: self.swapped,
+    pieces: self.pieces.clone(), over: self.over,
+    total: self.score.total, merges: self.score.merges.clone(),
+    max_chain: self.score.max_chain, object}
+}
+
+/// rebuild the world of the snapshot
+/// (every object is checked first, then every registered object is destroyed
+/// and each object of the snapshot is created again, catalogue polyhedra
+/// with new keys)
+pub fn restore(&mut self, s: &Snapshot) -> Result<(), String> {
+  if s.scene != self.scene.name() {
+    println!("snapshot of scene {} restored on {}", s.scene, self.scene.name());
+  }
+  let mut builds: Vec<(Role, Option<PE>)> = vec![];
+  for o in s.object.iter() {
+    let role = Role::from_name(&o.role)
+      .ok_or(format!("{}: role {}", o.key, o.role))?;
+    let pe = match (&o.pe, &o.def) {
+    (Some(n), _) =>
+      Some(PE::from_name(n).ok_or(format!("{}: unknown PE {}", o.key, n))?),
+    (None, Some(def)) => {
+      def.check().map_err(|e| format!("{}: {}", o.key, e))?;
+      None
+    },
+    (None, None) => { return Err(format!("{}: no definition", o.key)); }
+    };
+    builds.push((role, pe));
+  }
+  let mut refs: Vec<&String> = s.nexkeys.iter().chain(s.holdkey.iter())
+    .chain(s.pieces.iter()).collect();
+  if !s.current.is_empty() { refs.push(&s.current); }
+  if let Some(k) = refs.into_iter().find(|&k| s.object.iter().all(|o| o.key != *k)) {
+    return Err(format!("{}: not in the snapshot", k));
+  }
+  if Phase::from_usize(s.phase).is_none() {
+    return Err(format!("phase {}", s.phase));
+  }
+  let rng = match s.rng.as_str() {
+  "" => None, // keep the current stream
+  r => Some(parse_rng(r).ok_or(format!("rng {}", r))?)
+  };
+  let olds: Vec<dBodyID> = self.reg.iter().map(|(&id, _)| id).collect();
+  for id in olds { self.destroy(id); }
+  let mut keys: Vec<(String, String)> = vec![]; // (snapshot key, new key)
+  for (o, &(role, pe)) in s.object.iter().zip(builds.iter()) {
+    let p: dVector3 = [o.pos[0], o.pos[1], o.pos[2], 1.0];
+    let k = match (pe, &o.def) {
+    (Some(pe), _) => self.create_polyhedron(pe.0, p),
+    (None, def) => {
+      let mut def = def.clone().expect("checked above");
+      def.name = Some(o.key.clone());
+      match self.create_object(&def) {
+      Ok(k) => k,
+      Err(e) => { println!("{}: {}", o.key, e); continue; }
+      }
+    }
+    };
+    let rode = self.super_mut();
+    let Ok(obg) = rode.find_mut(k.clone()) else {
+      println!("{}: not created", k);
+      continue;
+    };
+    let (id, g) = (obg.body(), obg.geom());
+    obg.col = o.col;
+    if o.enabled { obg.enable(); } else { obg.disable(); }
+    if let Ok(mgm) = rode.get_mgm_mut(g) { mgm.get_tcm_mut().col = o.col; }
+    for (g, &[k, g_, c]) in krp_geoms(id, g).into_iter().zip(o.kgc.iter()) {
+      let Ok(mgm) = rode.get_mgm_mut(g) else { continue; };
+      let krp = mgm.get_krp_mut();
+      (krp.k, krp.g, krp.c) = (k, g_, c);
+    }
+    self.set_pos_Q(id, p, o.q);
+    Obg::set_linear_vel_by_id(id, &[o.lvel[0], o.lvel[1], o.lvel[2], 0.0]);
+    Obg::set_angular_vel_by_id(id, &[o.avel[0], o.avel[1], o.avel[2], 0.0]);
+    let tier = pe.and_then(|pe| self.score.tier(pe.key()));
+    let mut e = Entry::new(pe, tier, o.step, role);
+    e.chain = o.chain;
+    e.def = o.def.clone();
+    self.reg.insert(id, e);
+    keys.push((o.key.clone(), k));
+  }
+  let rekey = |k: &String| new_key(&keys, k);
+  self.current = new_key(&keys, &s.current);
+  self.nexkeys = s.nexkeys.iter().map(rekey).collect();
+  self.holdkey = s.holdkey.as_ref().map(rekey);
+  self.swapped = s.swapped;
+  self.pos = [s.pos[0], s.pos[1], s.pos[2], 1.0];
+  self.rot = s.object.iter().find(|o| o.key == s.current).map_or(QI, |o| o.q);
+  self.pieces = s.pieces.iter().map(rekey).collect();
+  self.phase = Phase::from_usize(s.phase).unwrap_or(PEmpty);
+  self.steps = s.steps;
+  if let Some((seed, pos)) = rng {
+    self.rng = ChaCha8Rng::seed_from_u64(seed);
+    self.rng.set_word_pos(pos);
+    self.seed = seed;
+  }
+  self.over = s.over;
+  self.score.total = s.total;
+  self.score.max_chain = s.max_chain;
+  for (m, &n) in self.score.merges.iter_mut().zip(s.merges.iter()) { *m = n; }
+  self.ebps.clear();
+  self.update_guide();
+  println!("snapshot: {} objects restored", keys.len());
+  Ok(())
+}
+
+/// write snapshot to the snapshot path
+pub fn save_snapshot(&mut self) {
+  let path = self.snap_path.clone();
+  match self.snapshot().save(&path) {
+  Err(e) => println!("{}", e),
+  Ok(()) => println!("snapshot saved: {}", path)
+  }
+}
+
+/// read snapshot from the snapshot path
+pub fn load_snapshot(&mut self) {
+  let r = Snapshot::load(&self.snap_path).and_then(|s| self.restore(&s));
+  if let Err(e) = r { println!("{}", e); }
+}
+
+/// number of drops that can be undone (0: undo disabled)
+pub fn set_undo_depth(&mut self, depth: usize) {
+  self.undo_depth = depth;
+  while self.undo.len() > depth { self.undo.pop_front(); }
+}
+
+/// take snapshot just before the release (oldest dropped over the depth)
+pub fn push_undo(&mut self) {
+  if self.undo_depth == 0 { return; }
+  let s = self.snapshot();
+  if self.undo.len() >= self.undo_depth { self.undo.pop_front(); }
+  self.undo.push_back(s);
+}
+
+/// restore the world and game state just before the last release
+/// (steps keep counting so that recorded commands stay in order)
+pub fn undo(&mut self) {
+  let Some(s) = self.undo.pop_back() else { println!("nothing to undo"); return; };
+  let steps = self.steps;
+  if let Err(e) = self.restore(&s) { println!("{}", e); }
+  self.steps = steps;
+  println!("undo ({} left)", self.undo.len());
+}
+
+}